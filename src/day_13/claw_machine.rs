@@ -1,3 +1,4 @@
+use crate::util::math;
 use crate::util::point_2d::Point2d;
 
 use std::string::ToString;
@@ -20,26 +21,23 @@ impl ClawMachine {
     }
 
     fn number_of_button_presses_to_prize(&self) -> Option<(i64, i64)> {
-        let b_presses_numerator =
-            self.a_button.x * self.prize_location.y - self.a_button.y * self.prize_location.x;
-        let b_presses_denominator =
-            self.a_button.x * self.b_button.y - self.a_button.y * self.b_button.x;
-
-        let can_press_b = b_presses_numerator % b_presses_denominator == 0;
-
-        let b_presses = b_presses_numerator / b_presses_denominator;
-
-        let a_presses_numerator = self.prize_location.x - self.b_button.x * b_presses;
-        let a_presses_denominator = self.a_button.x;
-
-        let can_press_a = a_presses_numerator % a_presses_denominator == 0;
-
-        let a_presses = a_presses_numerator / a_presses_denominator;
+        let solution = math::solve_2x2_linear_system(
+            [
+                [i128::from(self.a_button.x), i128::from(self.b_button.x)],
+                [i128::from(self.a_button.y), i128::from(self.b_button.y)],
+            ],
+            [
+                i128::from(self.prize_location.x),
+                i128::from(self.prize_location.y),
+            ],
+        );
 
-        if can_press_a && can_press_b {
-            Some((a_presses, b_presses))
-        } else {
-            None
+        match solution.integer_solution()?.as_slice() {
+            [a_presses, b_presses] if (*a_presses >= 0) && (*b_presses >= 0) => Some((
+                i64::try_from(*a_presses).ok()?,
+                i64::try_from(*b_presses).ok()?,
+            )),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::computer::OpCode;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AssemblyError {
+    UnknownMnemonic {
+        line: usize,
+        mnemonic: String,
+    },
+    InvalidOperand {
+        line: usize,
+        operand: String,
+    },
+    MissingOperand {
+        line: usize,
+    },
+    UnexpectedToken {
+        line: usize,
+        token: String,
+    },
+    DuplicateLabel {
+        line: usize,
+        label: String,
+    },
+    UnknownLabel {
+        line: usize,
+        label: String,
+    },
+    LabelOutOfRange {
+        line: usize,
+        label: String,
+        address: usize,
+    },
+    AddressMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssemblyError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {line}: unknown mnemonic `{mnemonic}`")
+            }
+            AssemblyError::InvalidOperand { line, operand } => {
+                write!(f, "line {line}: invalid operand `{operand}`")
+            }
+            AssemblyError::MissingOperand { line } => write!(f, "line {line}: missing operand"),
+            AssemblyError::UnexpectedToken { line, token } => {
+                write!(f, "line {line}: unexpected `{token}`")
+            }
+            AssemblyError::DuplicateLabel { line, label } => {
+                write!(f, "line {line}: label `{label}` is already defined")
+            }
+            AssemblyError::UnknownLabel { line, label } => {
+                write!(f, "line {line}: label `{label}` is never defined")
+            }
+            AssemblyError::LabelOutOfRange {
+                line,
+                label,
+                address,
+            } => write!(
+                f,
+                "line {line}: label `{label}` is at address {address}, but jnz can only reach 0-7"
+            ),
+            AssemblyError::AddressMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: instruction is at address {expected}, not {found}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct SourceInstruction<'a> {
+    line: usize,
+    opcode: OpCode,
+    operand: Option<&'a str>,
+}
+
+/// Turns assembly source into program values.
+///
+/// Every line holds at most one instruction, written as a mnemonic and an operand
+/// (`adv 3`). Anything after a `;` is a comment. A line may start with any number of
+/// `label:` definitions, which `jnz` can use as its operand. Combo operands may be written
+/// as the registers `a`, `b` and `c`, and `bxc` may leave out its ignored operand. A
+/// numeric label such as `4:` asserts the address of the instruction, so the output of the
+/// disassembler assembles back into the same program.
+///
+/// # Errors
+///
+/// If a line can't be assembled, naming the (1-based) line.
+pub fn assemble(source: &str) -> Result<Vec<usize>, AssemblyError> {
+    let mut labels = HashMap::new();
+    let mut instructions = Vec::new();
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let address = instructions.len() * 2;
+
+        let mut remaining = raw_line
+            .split_once(';')
+            .map_or(raw_line, |(code, _)| code)
+            .trim();
+
+        while let Some((label, rest)) = remaining.split_once(':') {
+            let label = label.trim();
+
+            if let Ok(found) = label.parse::<usize>() {
+                if found != address {
+                    return Err(AssemblyError::AddressMismatch {
+                        line,
+                        expected: address,
+                        found,
+                    });
+                }
+            } else if !is_label(label) {
+                break;
+            } else if labels.insert(label, address).is_some() {
+                return Err(AssemblyError::DuplicateLabel {
+                    line,
+                    label: label.to_string(),
+                });
+            }
+
+            remaining = rest.trim();
+        }
+
+        let mut tokens = remaining.split_whitespace();
+
+        let Some(mnemonic) = tokens.next() else {
+            continue;
+        };
+
+        let opcode = mnemonic
+            .parse::<OpCode>()
+            .map_err(|_| AssemblyError::UnknownMnemonic {
+                line,
+                mnemonic: mnemonic.to_string(),
+            })?;
+
+        let operand = tokens.next();
+
+        if let Some(token) = tokens.next() {
+            return Err(AssemblyError::UnexpectedToken {
+                line,
+                token: token.to_string(),
+            });
+        }
+
+        instructions.push(SourceInstruction {
+            line,
+            opcode,
+            operand,
+        });
+    }
+
+    instructions
+        .iter()
+        .map(|instruction| {
+            Ok([
+                usize::from(instruction.opcode),
+                encode_operand(instruction, &labels)?,
+            ])
+        })
+        .collect::<Result<Vec<[usize; 2]>, AssemblyError>>()
+        .map(|pairs| pairs.concat())
+}
+
+/// Formats program values the way the puzzle input lists them.
+pub fn program_line(program: &[usize]) -> String {
+    let values: Vec<String> = program.iter().map(ToString::to_string).collect();
+
+    format!("Program: {}", values.join(","))
+}
+
+fn is_label(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn encode_operand(
+    instruction: &SourceInstruction,
+    labels: &HashMap<&str, usize>,
+) -> Result<usize, AssemblyError> {
+    let line = instruction.line;
+
+    let Some(operand) = instruction.operand else {
+        return match instruction.opcode {
+            OpCode::Bxc => Ok(0),
+            _ => Err(AssemblyError::MissingOperand { line }),
+        };
+    };
+
+    let invalid_operand = || AssemblyError::InvalidOperand {
+        line,
+        operand: operand.to_string(),
+    };
+
+    match instruction.opcode {
+        OpCode::Jnz if is_label(operand) => {
+            let address = *labels
+                .get(operand)
+                .ok_or_else(|| AssemblyError::UnknownLabel {
+                    line,
+                    label: operand.to_string(),
+                })?;
+
+            if address > 7 {
+                return Err(AssemblyError::LabelOutOfRange {
+                    line,
+                    label: operand.to_string(),
+                    address,
+                });
+            }
+
+            Ok(address)
+        }
+        opcode if opcode.uses_combo_operand() => match operand.to_ascii_lowercase().as_str() {
+            "a" => Ok(4),
+            "b" => Ok(5),
+            "c" => Ok(6),
+            literal => literal
+                .parse()
+                .ok()
+                .filter(|value| *value < 7)
+                .ok_or_else(invalid_operand),
+        },
+        _ => operand
+            .parse()
+            .ok()
+            .filter(|value| *value < 8)
+            .ok_or_else(invalid_operand),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day_17::computer::Computer;
+    use crate::day_17::disassembler::disassemble;
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            ; Prints A in octal, lowest digit first.
+            start:
+                adv 3       ; A = A >> 3
+                out a
+                jnz start   ; loop until A is empty
+        ";
+
+        let result = assemble(source);
+
+        assert_eq!(result, Ok(vec![0, 3, 5, 4, 3, 0]));
+    }
+
+    #[test]
+    fn test_assemble_inline_labels_and_registers() {
+        let source = "
+            bst A
+            loop: bxl 1
+            cdv b
+            skip: other: bxc
+            out C
+            jnz skip
+        ";
+
+        let result = assemble(source);
+
+        assert_eq!(result, Ok(vec![2, 4, 1, 1, 7, 5, 4, 0, 5, 6, 3, 6]));
+    }
+
+    #[test]
+    fn test_assemble_disassembly_round_trip() {
+        let program = vec![2, 4, 1, 1, 7, 5, 0, 3, 1, 4, 4, 0, 5, 5, 3, 0];
+
+        let source = disassemble(&program)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        assert_eq!(assemble(&source), Ok(program));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(
+            assemble("adv 3\nmul 2"),
+            Err(AssemblyError::UnknownMnemonic {
+                line: 2,
+                mnemonic: String::from("mul"),
+            })
+        );
+        assert_eq!(
+            assemble("out 7"),
+            Err(AssemblyError::InvalidOperand {
+                line: 1,
+                operand: String::from("7"),
+            })
+        );
+        assert_eq!(
+            assemble("bxl 8"),
+            Err(AssemblyError::InvalidOperand {
+                line: 1,
+                operand: String::from("8"),
+            })
+        );
+        assert_eq!(
+            assemble("\nadv"),
+            Err(AssemblyError::MissingOperand { line: 2 })
+        );
+        assert_eq!(
+            assemble("adv 3 4"),
+            Err(AssemblyError::UnexpectedToken {
+                line: 1,
+                token: String::from("4"),
+            })
+        );
+        assert_eq!(
+            assemble("top: adv 3\ntop: out a"),
+            Err(AssemblyError::DuplicateLabel {
+                line: 2,
+                label: String::from("top"),
+            })
+        );
+        assert_eq!(
+            assemble("jnz nowhere"),
+            Err(AssemblyError::UnknownLabel {
+                line: 1,
+                label: String::from("nowhere"),
+            })
+        );
+        assert_eq!(
+            assemble("adv 1\nadv 1\nadv 1\nadv 1\nfar: out a\njnz far"),
+            Err(AssemblyError::LabelOutOfRange {
+                line: 6,
+                label: String::from("far"),
+                address: 8,
+            })
+        );
+        assert_eq!(
+            assemble("0: adv 3\n4: out a"),
+            Err(AssemblyError::AddressMismatch {
+                line: 2,
+                expected: 2,
+                found: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_assembly_error_to_string() {
+        let error = AssemblyError::UnknownLabel {
+            line: 3,
+            label: String::from("loop"),
+        };
+
+        assert_eq!(error.to_string(), "line 3: label `loop` is never defined");
+    }
+
+    #[test]
+    fn test_program_line() {
+        assert_eq!(
+            program_line(&[0, 3, 5, 4, 3, 0]),
+            String::from("Program: 0,3,5,4,3,0")
+        );
+    }
+
+    #[test]
+    fn test_program_line_runs_on_computer() {
+        let program = assemble("start: adv 3\nout a\njnz start").unwrap();
+
+        let input = [
+            String::from("Register A: 2024"),
+            String::from("Register B: 0"),
+            String::from("Register C: 0"),
+            String::new(),
+            program_line(&program),
+        ];
+
+        let mut computer = Computer::try_from(input.as_slice()).unwrap();
+
+        assert_eq!(computer.execute_stack(), "5,7,3,0");
+    }
+}
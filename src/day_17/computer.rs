@@ -1,7 +1,16 @@
+use std::fmt;
+use std::str::FromStr;
 use std::string::ToString;
 
+#[cfg(test)]
+use super::assembler::{self, AssemblyError};
+#[cfg(test)]
+use super::disassembler;
+#[cfg(test)]
+use super::symbolic::{self, SymbolicError, SymbolicRun};
+
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum OpCode {
+pub enum OpCode {
     Adv,
     Bxl,
     Bst,
@@ -12,6 +21,82 @@ enum OpCode {
     Cdv,
 }
 
+impl OpCode {
+    pub fn from_value(value: usize) -> Option<Self> {
+        match value {
+            0 => Some(OpCode::Adv),
+            1 => Some(OpCode::Bxl),
+            2 => Some(OpCode::Bst),
+            3 => Some(OpCode::Jnz),
+            4 => Some(OpCode::Bxc),
+            5 => Some(OpCode::Out),
+            6 => Some(OpCode::Bdv),
+            7 => Some(OpCode::Cdv),
+            _ => None,
+        }
+    }
+
+    pub fn uses_combo_operand(self) -> bool {
+        matches!(
+            self,
+            OpCode::Adv | OpCode::Bst | OpCode::Out | OpCode::Bdv | OpCode::Cdv
+        )
+    }
+}
+
+impl fmt::Display for OpCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mnemonic = match self {
+            OpCode::Adv => "adv",
+            OpCode::Bxl => "bxl",
+            OpCode::Bst => "bst",
+            OpCode::Jnz => "jnz",
+            OpCode::Bxc => "bxc",
+            OpCode::Out => "out",
+            OpCode::Bdv => "bdv",
+            OpCode::Cdv => "cdv",
+        };
+
+        write!(f, "{mnemonic}")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OpCodeParseError;
+
+impl FromStr for OpCode {
+    type Err = OpCodeParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.to_ascii_lowercase().as_str() {
+            "adv" => Ok(OpCode::Adv),
+            "bxl" => Ok(OpCode::Bxl),
+            "bst" => Ok(OpCode::Bst),
+            "jnz" => Ok(OpCode::Jnz),
+            "bxc" => Ok(OpCode::Bxc),
+            "out" => Ok(OpCode::Out),
+            "bdv" => Ok(OpCode::Bdv),
+            "cdv" => Ok(OpCode::Cdv),
+            _ => Err(OpCodeParseError),
+        }
+    }
+}
+
+impl From<OpCode> for usize {
+    fn from(input: OpCode) -> Self {
+        match input {
+            OpCode::Adv => 0,
+            OpCode::Bxl => 1,
+            OpCode::Bst => 2,
+            OpCode::Jnz => 3,
+            OpCode::Bxc => 4,
+            OpCode::Out => 5,
+            OpCode::Bdv => 6,
+            OpCode::Cdv => 7,
+        }
+    }
+}
+
 impl From<&usize> for OpCode {
     fn from(input: &usize) -> Self {
         Self::from(*input)
@@ -20,17 +105,8 @@ impl From<&usize> for OpCode {
 
 impl From<usize> for OpCode {
     fn from(input: usize) -> Self {
-        match input {
-            0 => OpCode::Adv,
-            1 => OpCode::Bxl,
-            2 => OpCode::Bst,
-            3 => OpCode::Jnz,
-            4 => OpCode::Bxc,
-            5 => OpCode::Out,
-            6 => OpCode::Bdv,
-            7 => OpCode::Cdv,
-            _ => panic!("Cannot convert `{input}` to an `OpCode`"),
-        }
+        OpCode::from_value(input)
+            .unwrap_or_else(|| panic!("Cannot convert `{input}` to an `OpCode`"))
     }
 }
 
@@ -46,26 +122,14 @@ impl OpStackItem {
     }
 }
 
-impl From<(&usize, &usize)> for OpStackItem {
-    fn from(input: (&usize, &usize)) -> OpStackItem {
-        Self::from((*input.0, *input.1))
-    }
-}
-
-impl From<(usize, usize)> for OpStackItem {
-    fn from(input: (usize, usize)) -> OpStackItem {
-        OpStackItem::new(OpCode::from(input.0), input.1)
-    }
-}
-
 #[derive(Debug, PartialEq, Copy, Clone)]
 struct OpStackItemExecutionResult {
-    operation_result: usize,
+    operation_result: Word,
     stack_pointer: usize,
 }
 
 impl OpStackItemExecutionResult {
-    fn new(operation_result: usize, stack_pointer: usize) -> Self {
+    fn new(operation_result: Word, stack_pointer: usize) -> Self {
         OpStackItemExecutionResult {
             operation_result,
             stack_pointer,
@@ -73,148 +137,571 @@ impl OpStackItemExecutionResult {
     }
 }
 
+/// Registers are stored in the widest supported word, 128 bits, and masked down to the
+/// [`WordSize`] the computer was set up with.
+pub type Word = u128;
+
+/// How many bits each register holds. Any width from 1 up to 128 bits works, but nothing
+/// wider, as registers are stored in a [`Word`]. Results that don't fit wrap around, and
+/// shifting by at least the word size gives zero.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct WordSize {
+    bits: u32,
+}
+
+impl WordSize {
+    pub const U64: Self = WordSize::bits(64);
+    pub const U128: Self = WordSize::bits(Word::BITS);
+
+    /// # Panics
+    ///
+    /// If `bits` is zero or more than 128.
+    pub const fn bits(bits: u32) -> Self {
+        assert!(
+            bits > 0 && bits <= Word::BITS,
+            "Word size must be between 1 and 128 bits!"
+        );
+
+        WordSize { bits }
+    }
+
+    fn mask(self, value: Word) -> Word {
+        if self.bits == Word::BITS {
+            value
+        } else {
+            value & ((1 << self.bits) - 1)
+        }
+    }
+
+    fn shift_right(self, value: Word, amount: Word) -> Word {
+        if amount >= Word::from(self.bits) {
+            0
+        } else {
+            value >> amount
+        }
+    }
+}
+
+impl Default for WordSize {
+    fn default() -> Self {
+        WordSize::U64
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Registers {
+    pub a: Word,
+    pub b: Word,
+    pub c: Word,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A={} B={} C={}", self.a, self.b, self.c)
+    }
+}
+
+/// What the computer looks like between two instructions.
+#[cfg(test)]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Snapshot {
+    pub registers: Registers,
+    pub instruction_pointer: usize,
+    pub output: Vec<usize>,
+}
+
+/// Stops [`Computer::run_until`] once the computer reaches a matching state. Breakpoints are
+/// checked after every instruction, so the instruction at the breakpoint hasn't run yet and
+/// running again carries on from there.
+#[cfg(test)]
+#[derive(Debug, Copy, Clone)]
+pub enum Breakpoint {
+    InstructionPointer(usize),
+    Condition(fn(&Registers) -> bool),
+}
+
+#[cfg(test)]
+impl Breakpoint {
+    fn is_hit_by(&self, registers: &Registers, instruction_pointer: usize) -> bool {
+        match self {
+            Breakpoint::InstructionPointer(pointer) => *pointer == instruction_pointer,
+            Breakpoint::Condition(condition) => condition(registers),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StopReason {
+    Halted,
+    /// Holds the index of the breakpoint that was hit.
+    Breakpoint(usize),
+    CycleLimit,
+    /// Holds the address of an instruction that can't be executed, like an unknown opcode or
+    /// the reserved combo operand 7.
+    InvalidInstruction(usize),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceEntry {
+    pub instruction_pointer: usize,
+    pub opcode: OpCode,
+    pub operand: usize,
+    pub before: Registers,
+    pub after: Registers,
+    pub output: Option<usize>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} | {} -> {}",
+            self.instruction_pointer, self.opcode, self.operand, self.before, self.after
+        )?;
+
+        if let Some(value) = self.output {
+            write!(f, " | out {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+const MAX_QUINE_BITS_PER_OUTPUT: u32 = 8;
+const QUINE_SEARCH_CYCLE_LIMIT: usize = 100_000;
+
+#[derive(Debug, PartialEq)]
+pub struct QuineSearchError;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ComputerInputError {
+    LineCount(usize),
+    InvalidRegister { line: usize, value: String },
+    RegisterOverflow { line: usize, value: Word, bits: u32 },
+}
+
+impl fmt::Display for ComputerInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputerInputError::LineCount(count) => {
+                write!(f, "expected five lines, found {count}")
+            }
+            ComputerInputError::InvalidRegister { line, value } => {
+                write!(f, "line {line}: `{value}` is not a register value")
+            }
+            ComputerInputError::RegisterOverflow { line, value, bits } => {
+                write!(f, "line {line}: {value} doesn't fit in {bits} bits")
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Computer {
-    register_a: usize,
-    register_b: usize,
-    register_c: usize,
+    register_a: Word,
+    register_b: Word,
+    register_c: Word,
     stack_pointer: usize,
     stack: Vec<usize>,
     output: Vec<usize>,
+    word_size: WordSize,
+    invalid_instruction_at: Option<usize>,
 }
 
 impl Computer {
+    /// Builds a computer with the given registers and a program written in assembly (see
+    /// [`assembler::assemble`]), going through the same input lines as the puzzle.
+    ///
+    /// # Errors
+    ///
+    /// If the program doesn't assemble.
+    #[cfg(test)]
+    pub fn from_assembly(registers: [u64; 3], source: &str) -> Result<Self, AssemblyError> {
+        let program = assembler::assemble(source)?;
+
+        let [register_a, register_b, register_c] = registers;
+
+        let input = [
+            format!("Register A: {register_a}"),
+            format!("Register B: {register_b}"),
+            format!("Register C: {register_c}"),
+            String::new(),
+            assembler::program_line(&program),
+        ];
+
+        Ok(Computer::try_from(input.as_slice())
+            .expect("Assembled programs and 64-bit registers are valid input!"))
+    }
+
+    /// Builds a computer whose registers hold `word_size` bits, rejecting register values that
+    /// don't fit rather than truncating them.
+    ///
+    /// # Errors
+    ///
+    /// If a register doesn't fit, naming the line it would be on in the puzzle input.
+    pub fn try_from_with_word_size(
+        registers: [Word; 3],
+        stack: Vec<usize>,
+        word_size: WordSize,
+    ) -> Result<Self, ComputerInputError> {
+        if let Some((index, value)) = registers
+            .into_iter()
+            .enumerate()
+            .find(|(_, value)| word_size.mask(*value) != *value)
+        {
+            return Err(ComputerInputError::RegisterOverflow {
+                line: index + 1,
+                value,
+                bits: word_size.bits,
+            });
+        }
+
+        let [register_a, register_b, register_c] = registers;
+
+        Ok(Computer {
+            register_a,
+            register_b,
+            register_c,
+            stack_pointer: 0,
+            stack,
+            output: Vec::new(),
+            word_size,
+            invalid_instruction_at: None,
+        })
+    }
+
+    /// Switches to `word_size`, truncating the registers if they don't fit.
+    #[must_use]
+    pub fn with_word_size(mut self, word_size: WordSize) -> Self {
+        self.word_size = word_size;
+        self.register_a = word_size.mask(self.register_a);
+        self.register_b = word_size.mask(self.register_b);
+        self.register_c = word_size.mask(self.register_c);
+
+        self
+    }
+
     pub fn execute_stack(&mut self) -> String {
         while self.execute_next_item() {}
 
         Self::to_comma_joined_string(&self.output)
     }
 
-    pub fn lowest_register_a_value_to_produce_program_copy(&self) -> usize {
-        let stack_without_jump = self.stack[0..(self.stack.len() - 2)].to_vec();
+    /// Lists every instruction in the program with its address, mnemonic and what it does.
+    #[cfg(test)]
+    pub fn disassemble(&self) -> String {
+        disassembler::disassemble(&self.stack)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Finds the lowest value for register A that makes the program output a copy of itself.
+    ///
+    /// Assumes only that the last outputs depend on the highest bits of A, which holds for
+    /// any program that consumes a fixed number of bits of A per output. The bits are
+    /// guessed a chunk at a time, most significant first, keeping only guesses that produce
+    /// the end of the program, for every chunk size up to [`MAX_QUINE_BITS_PER_OUTPUT`].
+    ///
+    /// # Errors
+    ///
+    /// If no value of register A produces a copy of the program.
+    pub fn lowest_register_a_value_to_produce_program_copy(
+        &self,
+    ) -> Result<Word, QuineSearchError> {
+        (1..=MAX_QUINE_BITS_PER_OUTPUT)
+            .filter_map(|bits_per_output| self.search_quine(0, bits_per_output, 0))
+            .min()
+            .ok_or(QuineSearchError)
+    }
 
-        let mut clone = self.clone();
+    fn search_quine(
+        &self,
+        prefix: Word,
+        bits_per_output: u32,
+        outputs_matched: usize,
+    ) -> Option<Word> {
+        if outputs_matched == self.stack.len() {
+            return Some(prefix);
+        }
 
-        clone.stack = stack_without_jump;
+        if Word::BITS - prefix.leading_zeros() + bits_per_output > self.word_size.bits {
+            return None;
+        }
 
-        let mut result = 0;
+        let expected_output = &self.stack[(self.stack.len() - outputs_matched - 1)..];
 
-        for required_output in self.stack.iter().rev() {
-            result *= 8;
+        (0..(1 << bits_per_output)).find_map(|chunk| {
+            let candidate = (prefix << bits_per_output) | chunk;
 
-            loop {
-                clone.reset_with_register_a(result);
+            let mut computer = self.with_register_a(candidate);
 
-                clone.execute_stack();
+            let halted = computer.run_for(QUINE_SEARCH_CYCLE_LIMIT) == StopReason::Halted;
 
-                match clone.output.first() {
-                    Some(current_output) if current_output == required_output => {
-                        break;
-                    }
-                    Some(_) => {
-                        result += 1;
-                    }
-                    None => panic!("Something went horribly wrong lol"),
-                }
+            if halted && computer.output == expected_output {
+                self.search_quine(candidate, bits_per_output, outputs_matched + 1)
+            } else {
+                None
             }
+        })
+    }
+
+    /// Runs the program with the lowest `a_bits` bits of register A unknown, describing each
+    /// output as an expression over them (see [`symbolic::execute`]).
+    ///
+    /// # Errors
+    ///
+    /// If the program branches into too many paths.
+    ///
+    /// # Panics
+    ///
+    /// If `a_bits` is wider than the word size.
+    #[cfg(test)]
+    pub fn run_symbolic(&self, a_bits: u32) -> Result<SymbolicRun, SymbolicError> {
+        assert!(
+            a_bits <= self.word_size.bits,
+            "Register A can't be wider than the word size!"
+        );
+
+        symbolic::execute(&self.stack, self.register_b, self.register_c, a_bits)
+    }
+
+    fn with_register_a(&self, register_a: Word) -> Self {
+        let mut clone = self.clone();
+
+        clone.register_a = self.word_size.mask(register_a);
+        clone.stack_pointer = 0;
+        clone.output = Vec::new();
+        clone.invalid_instruction_at = None;
+
+        clone
+    }
+
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.register_a,
+            b: self.register_b,
+            c: self.register_c,
         }
+    }
+
+    #[cfg(test)]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            registers: self.registers(),
+            instruction_pointer: self.stack_pointer,
+            output: self.output.clone(),
+        }
+    }
 
-        clone.reset_with_register_a(result);
-        clone.stack.clone_from(&self.stack);
-        clone.execute_stack();
+    /// Executes the next instruction, or returns `None` if the program has halted.
+    #[cfg(test)]
+    pub fn step(&mut self) -> Option<Snapshot> {
+        self.execute_next_item().then(|| self.snapshot())
+    }
 
-        result
+    /// Runs until the program halts or `cycle_limit` instructions have been executed.
+    fn run_for(&mut self, cycle_limit: usize) -> StopReason {
+        self.run_with(cycle_limit, |_, _| None, |_| {})
     }
 
-    fn reset_with_register_a(&mut self, register_a: usize) {
-        self.register_a = register_a;
-        self.register_b = 0;
-        self.register_c = 0;
-        self.stack_pointer = 0;
-        self.output = Vec::new();
+    /// Runs until the program halts, a breakpoint is hit, or `cycle_limit` instructions have
+    /// been executed.
+    #[cfg(test)]
+    pub fn run_until(&mut self, breakpoints: &[Breakpoint], cycle_limit: usize) -> StopReason {
+        self.run_with(cycle_limit, Self::breakpoint_hit(breakpoints), |_| {})
     }
 
-    fn execute_next_item(&mut self) -> bool {
-        if let Some(current_stack_item) = self.get_stack_item(self.stack_pointer) {
-            let execution_result = self.execute_item(current_stack_item);
-
-            match current_stack_item.opcode {
-                OpCode::Adv => {
-                    self.register_a = execution_result.operation_result;
-                }
-                OpCode::Bxl | OpCode::Bst | OpCode::Bxc | OpCode::Bdv => {
-                    self.register_b = execution_result.operation_result;
-                }
-                OpCode::Out => {
-                    self.output.push(execution_result.operation_result);
-                }
-                OpCode::Cdv => {
-                    self.register_c = execution_result.operation_result;
-                }
-                OpCode::Jnz => {}
-            };
-
-            self.stack_pointer = execution_result.stack_pointer;
-
-            true
+    /// Same as [`Computer::run_until`], but also records every instruction executed.
+    #[cfg(test)]
+    pub fn trace(
+        &mut self,
+        breakpoints: &[Breakpoint],
+        cycle_limit: usize,
+    ) -> (Vec<TraceEntry>, StopReason) {
+        let mut entries = Vec::new();
+
+        let stop_reason = self.run_with(cycle_limit, Self::breakpoint_hit(breakpoints), |entry| {
+            entries.push(entry);
+        });
+
+        (entries, stop_reason)
+    }
+
+    /// Finds the index of the first breakpoint hit by the registers and instruction pointer.
+    #[cfg(test)]
+    fn breakpoint_hit(
+        breakpoints: &[Breakpoint],
+    ) -> impl Fn(&Registers, usize) -> Option<usize> + '_ {
+        |registers, instruction_pointer| {
+            breakpoints
+                .iter()
+                .position(|breakpoint| breakpoint.is_hit_by(registers, instruction_pointer))
+        }
+    }
+
+    /// Runs for up to `cycle_limit` instructions, passing each one to `on_step`. After each
+    /// instruction, `breakpoint_hit` says which breakpoint, if any, stops the computer.
+    fn run_with<B, F>(
+        &mut self,
+        cycle_limit: usize,
+        breakpoint_hit: B,
+        mut on_step: F,
+    ) -> StopReason
+    where
+        B: Fn(&Registers, usize) -> Option<usize>,
+        F: FnMut(TraceEntry),
+    {
+        for _ in 0..cycle_limit {
+            let instruction_pointer = self.stack_pointer;
+            let before = self.registers();
+            let output_length = self.output.len();
+
+            if !self.execute_next_item() {
+                return match self.invalid_instruction_at {
+                    Some(address) => StopReason::InvalidInstruction(address),
+                    None => StopReason::Halted,
+                };
+            }
+
+            let item = self
+                .get_stack_item(instruction_pointer)
+                .expect("Instruction was just executed!");
+
+            on_step(TraceEntry {
+                instruction_pointer,
+                opcode: item.opcode,
+                operand: item.operand,
+                before,
+                after: self.registers(),
+                output: self.output.get(output_length).copied(),
+            });
+
+            if let Some(index) = breakpoint_hit(&self.registers(), self.stack_pointer) {
+                return StopReason::Breakpoint(index);
+            }
+        }
+
+        if self.stack_pointer + 1 >= self.stack.len() {
+            StopReason::Halted
+        } else if self.invalid_instruction_at.is_some() || !self.can_execute_next_item() {
+            StopReason::InvalidInstruction(self.stack_pointer)
         } else {
-            false
+            StopReason::CycleLimit
         }
     }
 
-    fn execute_item(&self, item: OpStackItem) -> OpStackItemExecutionResult {
-        match item.opcode {
-            OpCode::Adv | OpCode::Bdv | OpCode::Cdv => {
-                let denominator = 2_usize.pow(self.combo_operand(item.operand).try_into().unwrap());
+    fn can_execute_next_item(&self) -> bool {
+        self.get_stack_item(self.stack_pointer)
+            .and_then(|item| self.execute_item(item))
+            .is_some()
+    }
+
+    /// Returns `false` once the program halts, either by running off the end or by reaching
+    /// an instruction it can't execute.
+    fn execute_next_item(&mut self) -> bool {
+        if self.invalid_instruction_at.is_some() || (self.stack_pointer + 1 >= self.stack.len()) {
+            return false;
+        }
+
+        let Some((current_stack_item, execution_result)) = self
+            .get_stack_item(self.stack_pointer)
+            .and_then(|item| self.execute_item(item).map(|result| (item, result)))
+        else {
+            self.invalid_instruction_at = Some(self.stack_pointer);
+
+            return false;
+        };
 
-                OpStackItemExecutionResult::new(
-                    self.register_a / denominator,
-                    self.stack_pointer + 2,
-                )
+        match current_stack_item.opcode {
+            OpCode::Adv => {
+                self.register_a = self.word_size.mask(execution_result.operation_result);
             }
-            OpCode::Bxl => OpStackItemExecutionResult::new(
-                self.register_b ^ item.operand,
-                self.stack_pointer + 2,
+            OpCode::Bxl | OpCode::Bst | OpCode::Bxc | OpCode::Bdv => {
+                self.register_b = self.word_size.mask(execution_result.operation_result);
+            }
+            OpCode::Out => {
+                self.output.push(
+                    usize::try_from(execution_result.operation_result)
+                        .expect("Output is only 3 bits!"),
+                );
+            }
+            OpCode::Cdv => {
+                self.register_c = self.word_size.mask(execution_result.operation_result);
+            }
+            OpCode::Jnz => {}
+        }
+
+        self.stack_pointer = execution_result.stack_pointer;
+
+        true
+    }
+
+    fn execute_item(&self, item: OpStackItem) -> Option<OpStackItemExecutionResult> {
+        let next_pointer = self.stack_pointer + 2;
+
+        let result = match item.opcode {
+            OpCode::Adv | OpCode::Bdv | OpCode::Cdv => OpStackItemExecutionResult::new(
+                self.word_size
+                    .shift_right(self.register_a, self.combo_operand(item.operand)?),
+                next_pointer,
             ),
-            OpCode::Bst | OpCode::Out => OpStackItemExecutionResult::new(
-                self.combo_operand(item.operand) % 8,
-                self.stack_pointer + 2,
+            OpCode::Bxl => OpStackItemExecutionResult::new(
+                self.register_b ^ Self::literal_operand(item.operand)?,
+                next_pointer,
             ),
+            OpCode::Bst | OpCode::Out => {
+                OpStackItemExecutionResult::new(self.combo_operand(item.operand)? % 8, next_pointer)
+            }
             OpCode::Jnz if self.register_a != 0 => OpStackItemExecutionResult::new(0, item.operand),
-            OpCode::Jnz => OpStackItemExecutionResult::new(0, self.stack_pointer + 2),
-            OpCode::Bxc => OpStackItemExecutionResult::new(
-                self.register_b ^ self.register_c,
-                self.stack_pointer + 2,
-            ),
-        }
+            OpCode::Jnz => OpStackItemExecutionResult::new(0, next_pointer),
+            OpCode::Bxc => {
+                OpStackItemExecutionResult::new(self.register_b ^ self.register_c, next_pointer)
+            }
+        };
+
+        Some(result)
     }
 
+    /// Returns `None` past the end of the program or if the opcode is unknown.
     fn get_stack_item(&self, pointer: usize) -> Option<OpStackItem> {
-        self.stack
-            .get(pointer)
-            .zip(self.stack.get(pointer + 1))
-            .map(OpStackItem::from)
+        let opcode = OpCode::from_value(*self.stack.get(pointer)?)?;
+        let operand = *self.stack.get(pointer + 1)?;
+
+        Some(OpStackItem::new(opcode, operand))
+    }
+
+    /// Returns `None` for anything that isn't 3 bits.
+    fn literal_operand(literal_operand: usize) -> Option<Word> {
+        Word::try_from(literal_operand)
+            .ok()
+            .filter(|operand| *operand < 8)
     }
 
-    fn combo_operand(&self, combo_operand: usize) -> usize {
+    /// Returns `None` for the reserved combo operand 7, or anything that isn't 3 bits.
+    fn combo_operand(&self, combo_operand: usize) -> Option<Word> {
         match combo_operand {
-            0 => 0,
-            1 => 1,
-            2 => 2,
-            3 => 3,
-            4 => self.register_a,
-            5 => self.register_b,
-            6 => self.register_c,
-            _ => panic!("{combo_operand} is not a valid operand!"),
+            0 => Some(0),
+            1 => Some(1),
+            2 => Some(2),
+            3 => Some(3),
+            4 => Some(self.register_a),
+            5 => Some(self.register_b),
+            6 => Some(self.register_c),
+            _ => None,
         }
     }
 
-    fn parse_register_input(input: &str) -> usize {
+    fn parse_register_input(input: &str, line: usize) -> Result<Word, ComputerInputError> {
         let (_, rhs_info) = input.split_once(": ").unwrap_or_default();
 
         rhs_info
             .parse()
-            .unwrap_or_else(|_| panic!("{rhs_info} is not a digit!"))
+            .map_err(|_| ComputerInputError::InvalidRegister {
+                line,
+                value: rhs_info.to_string(),
+            })
     }
 
     fn parse_stack_input(input: &str) -> Vec<usize> {
@@ -232,35 +719,37 @@ impl Computer {
     }
 }
 
-impl<const N: usize> From<[&str; N]> for Computer {
-    fn from(input: [&str; N]) -> Self {
+impl<const N: usize> TryFrom<[&str; N]> for Computer {
+    type Error = ComputerInputError;
+
+    fn try_from(input: [&str; N]) -> Result<Self, Self::Error> {
         let input_strings: Vec<String> = input.iter().map(ToString::to_string).collect();
 
-        Self::from(input_strings.as_slice())
+        Self::try_from(input_strings.as_slice())
     }
 }
 
-impl From<&[String]> for Computer {
-    fn from(input: &[String]) -> Self {
-        assert_eq!(input.len(), 5, "{input:?} must be have five lines!");
+/// Reads the registers and the program, rejecting register values that don't fit the
+/// default [`WordSize`] rather than truncating them.
+impl TryFrom<&[String]> for Computer {
+    type Error = ComputerInputError;
 
-        let register_a = Computer::parse_register_input(&input[0]);
-        let register_b = Computer::parse_register_input(&input[1]);
-        let register_c = Computer::parse_register_input(&input[2]);
+    fn try_from(input: &[String]) -> Result<Self, Self::Error> {
+        if input.len() != 5 {
+            return Err(ComputerInputError::LineCount(input.len()));
+        }
 
-        let stack_pointer = 0;
-        let stack = Computer::parse_stack_input(&input[4]);
+        let register_a = Computer::parse_register_input(&input[0], 1)?;
+        let register_b = Computer::parse_register_input(&input[1], 2)?;
+        let register_c = Computer::parse_register_input(&input[2], 3)?;
 
-        let output = Vec::new();
+        let stack = Computer::parse_stack_input(&input[4]);
 
-        Computer {
-            register_a,
-            register_b,
-            register_c,
-            stack_pointer,
+        Computer::try_from_with_word_size(
+            [register_a, register_b, register_c],
             stack,
-            output,
-        }
+            WordSize::default(),
+        )
     }
 }
 
@@ -277,28 +766,32 @@ mod tests {
             stack_pointer: 0,
             stack: vec![0, 1, 5, 4, 3, 0],
             output: Vec::new(),
+            word_size: WordSize::U64,
+            invalid_instruction_at: None,
         };
 
-        let result = Computer::from([
+        let result = Computer::try_from([
             "Register A: 729",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 0,1,5,4,3,0",
-        ]);
+        ])
+        .unwrap();
 
         assert_eq!(result, expected);
     }
 
     #[test]
     fn test_get_stack_item() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 729",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 0,1,5,4,3,0",
-        ]);
+        ])
+        .unwrap();
 
         let expected_1 = OpStackItem::new(OpCode::Adv, 1);
         let expected_2 = OpStackItem::new(OpCode::Bxc, 3);
@@ -317,21 +810,22 @@ mod tests {
 
     #[test]
     fn test_combo_operand() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 729",
             "Register B: 1012",
             "Register C: 9",
             "",
             "Program: 0,1,5,4,3,0",
-        ]);
+        ])
+        .unwrap();
 
-        let expected_0 = 0;
-        let expected_1 = 1;
-        let expected_2 = 2;
-        let expected_3 = 3;
-        let expected_4 = 729;
-        let expected_5 = 1012;
-        let expected_6 = 9;
+        let expected_0 = Some(0);
+        let expected_1 = Some(1);
+        let expected_2 = Some(2);
+        let expected_3 = Some(3);
+        let expected_4 = Some(729);
+        let expected_5 = Some(1012);
+        let expected_6 = Some(9);
 
         let result_0 = computer.combo_operand(0);
         let result_1 = computer.combo_operand(1);
@@ -351,34 +845,35 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "7 is not a valid operand!")]
-    fn test_combo_operand_panics_at_7() {
-        let computer = Computer::from([
+    fn test_combo_operand_rejects_7() {
+        let computer = Computer::try_from([
             "Register A: 729",
             "Register B: 1012",
             "Register C: 9",
             "",
             "Program: 0,1,5,4,3,0",
-        ]);
+        ])
+        .unwrap();
 
-        computer.combo_operand(7);
+        assert_eq!(computer.combo_operand(7), None);
     }
 
     #[test]
     fn test_execute_item_adv() {
-        let mut computer = Computer::from([
+        let mut computer = Computer::try_from([
             "Register A: 2024",
             "Register B: 0",
             "Register C: 2",
             "",
             "Program: 0,2",
-        ]);
+        ])
+        .unwrap();
 
         computer.stack_pointer = 10;
 
         let item = OpStackItem::new(OpCode::Adv, 6);
 
-        let expected = OpStackItemExecutionResult::new(506, 12);
+        let expected = Some(OpStackItemExecutionResult::new(506, 12));
 
         let result = computer.execute_item(item);
 
@@ -387,17 +882,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_bxl() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 0",
             "Register B: 29",
             "Register C: 0",
             "",
             "Program: 1,7",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Bxl, 7);
 
-        let expected = OpStackItemExecutionResult::new(26, 2);
+        let expected = Some(OpStackItemExecutionResult::new(26, 2));
 
         let result = computer.execute_item(item);
 
@@ -406,17 +902,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_bst() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 0",
             "Register B: 0",
             "Register C: 9",
             "",
             "Program: 2,6",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Bst, 6);
 
-        let expected = OpStackItemExecutionResult::new(1, 2);
+        let expected = Some(OpStackItemExecutionResult::new(1, 2));
 
         let result = computer.execute_item(item);
 
@@ -425,17 +922,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_jnz_no_jump() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 0",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 3,3",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Jnz, 0);
 
-        let expected = OpStackItemExecutionResult::new(0, 2);
+        let expected = Some(OpStackItemExecutionResult::new(0, 2));
 
         let result = computer.execute_item(item);
 
@@ -444,19 +942,20 @@ mod tests {
 
     #[test]
     fn test_execute_item_jnz_jump() {
-        let mut computer = Computer::from([
+        let mut computer = Computer::try_from([
             "Register A: 1",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 3,3,5,0,5,0",
-        ]);
+        ])
+        .unwrap();
 
         computer.stack_pointer = 4;
 
         let item = OpStackItem::new(OpCode::Jnz, 3);
 
-        let expected = OpStackItemExecutionResult::new(0, 3);
+        let expected = Some(OpStackItemExecutionResult::new(0, 3));
 
         let result = computer.execute_item(item);
 
@@ -465,17 +964,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_bxc() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 0",
             "Register B: 2024",
             "Register C: 43690",
             "",
             "Program: 4,0",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Bxc, 0);
 
-        let expected = OpStackItemExecutionResult::new(44_354, 2);
+        let expected = Some(OpStackItemExecutionResult::new(44_354, 2));
 
         let result = computer.execute_item(item);
 
@@ -484,17 +984,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_out() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 1012",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 5,4",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Out, 4);
 
-        let expected = OpStackItemExecutionResult::new(4, 2);
+        let expected = Some(OpStackItemExecutionResult::new(4, 2));
 
         let result = computer.execute_item(item);
 
@@ -503,17 +1004,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_bdv() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 2024",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 6,3",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Bdv, 3);
 
-        let expected = OpStackItemExecutionResult::new(253, 2);
+        let expected = Some(OpStackItemExecutionResult::new(253, 2));
 
         let result = computer.execute_item(item);
 
@@ -522,17 +1024,18 @@ mod tests {
 
     #[test]
     fn test_execute_item_cdv() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 809",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 7,2",
-        ]);
+        ])
+        .unwrap();
 
         let item = OpStackItem::new(OpCode::Cdv, 2);
 
-        let expected = OpStackItemExecutionResult::new(202, 2);
+        let expected = Some(OpStackItemExecutionResult::new(202, 2));
 
         let result = computer.execute_item(item);
 
@@ -541,41 +1044,46 @@ mod tests {
 
     #[test]
     fn test_execute_stack() {
-        let mut computer_1 = Computer::from([
+        let mut computer_1 = Computer::try_from([
             "Register A: 0",
             "Register B: 0",
             "Register C: 9",
             "",
             "Program: 2,6",
-        ]);
-        let mut computer_2 = Computer::from([
+        ])
+        .unwrap();
+        let mut computer_2 = Computer::try_from([
             "Register A: 10",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 5,0,5,1,5,4",
-        ]);
-        let mut computer_3 = Computer::from([
+        ])
+        .unwrap();
+        let mut computer_3 = Computer::try_from([
             "Register A: 2024",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 0,1,5,4,3,0",
-        ]);
-        let mut computer_4 = Computer::from([
+        ])
+        .unwrap();
+        let mut computer_4 = Computer::try_from([
             "Register A: 0",
             "Register B: 29",
             "Register C: 0",
             "",
             "Program: 1,7",
-        ]);
-        let mut computer_5 = Computer::from([
+        ])
+        .unwrap();
+        let mut computer_5 = Computer::try_from([
             "Register A: 0",
             "Register B: 2024",
             "Register C: 43690",
             "",
             "Program: 4,0",
-        ]);
+        ])
+        .unwrap();
 
         let expected_1 = String::new();
         let expected_2 = String::from("0,1,2");
@@ -607,17 +1115,418 @@ mod tests {
 
     #[test]
     fn test_lowest_register_a_value_to_produce_program_copy() {
-        let computer = Computer::from([
+        let computer = Computer::try_from([
             "Register A: 2024",
             "Register B: 0",
             "Register C: 0",
             "",
             "Program: 0,3,5,4,3,0",
-        ]);
+        ])
+        .unwrap();
+
+        assert_eq!(
+            computer.lowest_register_a_value_to_produce_program_copy(),
+            Ok(117_440)
+        );
+    }
+
+    #[test]
+    fn test_lowest_register_a_value_to_produce_program_copy_other_shifts() {
+        // Each output uses 4 bits of A.
+        let four_bits = Computer::from_assembly([0, 0, 0], "out a\nadv 3\nadv 1\njnz 0").unwrap();
+        // Each output uses 5 bits of A.
+        let five_bits = Computer::from_assembly([0, 0, 0], "out a\nadv 3\nadv 2\njnz 0").unwrap();
+
+        assert_eq!(
+            four_bits.lowest_register_a_value_to_produce_program_copy(),
+            Ok(2_198_876_229)
+        );
+        assert_eq!(
+            five_bits.lowest_register_a_value_to_produce_program_copy(),
+            Ok(278_166_339_717)
+        );
+
+        let mut clone = five_bits.with_register_a(278_166_339_717);
+
+        assert_eq!(clone.execute_stack(), "5,4,0,3,0,2,3,0");
+    }
+
+    #[test]
+    fn test_lowest_register_a_value_to_produce_program_copy_no_solution() {
+        // Always outputs 0 twice, so it can never copy itself.
+        let computer = Computer::from_assembly([0, 0, 0], "bxl 0\nout b\nout b").unwrap();
 
         assert_eq!(
             computer.lowest_register_a_value_to_produce_program_copy(),
-            117_440
+            Err(QuineSearchError)
+        );
+    }
+
+    #[test]
+    fn test_opcode_from_value() {
+        assert_eq!(OpCode::from_value(0), Some(OpCode::Adv));
+        assert_eq!(OpCode::from_value(7), Some(OpCode::Cdv));
+        assert_eq!(OpCode::from_value(8), None);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let computer = Computer::try_from([
+            "Register A: 2024",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 0,3,5,4,3,0",
+        ])
+        .unwrap();
+
+        let expected = [
+            "0: adv 3  ; A = A >> combo(3)=3",
+            "2: out 4  ; output combo(4)=A % 8",
+            "4: jnz 0  ; if A != 0 jump to 0",
+        ]
+        .join("\n");
+
+        assert_eq!(computer.disassemble(), expected);
+    }
+
+    #[test]
+    fn test_opcode_from_str() {
+        assert_eq!("bxc".parse(), Ok(OpCode::Bxc));
+        assert_eq!("CDV".parse(), Ok(OpCode::Cdv));
+        assert_eq!("nop".parse::<OpCode>(), Err(OpCodeParseError));
+    }
+
+    #[test]
+    fn test_from_assembly() {
+        let expected = Computer::try_from([
+            "Register A: 2024",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 0,3,5,4,3,0",
+        ])
+        .unwrap();
+
+        let result = Computer::from_assembly([2024, 0, 0], "loop: adv 3\nout a\njnz loop");
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut computer = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,0,5,1,5,4",
+        ])
+        .unwrap();
+
+        let expected_1 = Snapshot {
+            registers: Registers { a: 10, b: 0, c: 0 },
+            instruction_pointer: 2,
+            output: vec![0],
+        };
+        let expected_3 = Snapshot {
+            registers: Registers { a: 10, b: 0, c: 0 },
+            instruction_pointer: 6,
+            output: vec![0, 1, 2],
+        };
+
+        assert_eq!(computer.step(), Some(expected_1));
+        assert!(computer.step().is_some());
+        assert_eq!(computer.step(), Some(expected_3));
+        assert_eq!(computer.step(), None);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut computer = Computer::try_from([
+            "Register A: 2024",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 0,1,5,4,3,0",
+        ])
+        .unwrap();
+
+        let (entries, stop_reason) = computer.trace(&[], 100);
+
+        let expected_first = TraceEntry {
+            instruction_pointer: 0,
+            opcode: OpCode::Adv,
+            operand: 1,
+            before: Registers {
+                a: 2024,
+                b: 0,
+                c: 0,
+            },
+            after: Registers {
+                a: 1012,
+                b: 0,
+                c: 0,
+            },
+            output: None,
+        };
+
+        assert_eq!(stop_reason, StopReason::Halted);
+        assert_eq!(entries.len(), 33);
+        assert_eq!(entries[0], expected_first);
+        assert_eq!(
+            entries[1].to_string(),
+            "2: out 4 | A=1012 B=0 C=0 -> A=1012 B=0 C=0 | out 4"
+        );
+        assert_eq!(
+            entries[32].to_string(),
+            "4: jnz 0 | A=0 B=0 C=0 -> A=0 B=0 C=0"
+        );
+    }
+
+    #[test]
+    fn test_run_until_cycle_limit() {
+        // A is never changed, so this never halts.
+        let mut computer = Computer::try_from([
+            "Register A: 1",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,3,0",
+        ])
+        .unwrap();
+
+        assert_eq!(computer.run_until(&[], 1_000), StopReason::CycleLimit);
+        assert_eq!(computer.output.len(), 500);
+        assert_eq!(computer.run_until(&[], 0), StopReason::CycleLimit);
+    }
+
+    #[test]
+    fn test_run_until_breakpoints() {
+        let mut computer = Computer::try_from([
+            "Register A: 2024",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 0,1,5,4,3,0",
+        ])
+        .unwrap();
+
+        let breakpoints = [
+            Breakpoint::Condition(|registers| registers.a < 100),
+            Breakpoint::InstructionPointer(4),
+        ];
+
+        assert_eq!(
+            computer.run_until(&breakpoints, 100),
+            StopReason::Breakpoint(1)
+        );
+        assert_eq!(computer.snapshot().instruction_pointer, 4);
+
+        assert_eq!(
+            computer.run_until(&breakpoints[..1], 100),
+            StopReason::Breakpoint(0)
+        );
+        assert_eq!(computer.registers().a, 63);
+
+        assert_eq!(computer.run_until(&[], 100), StopReason::Halted);
+        assert_eq!(computer.execute_stack(), "4,2,5,6,7,7,7,7,3,1,0");
+    }
+
+    #[test]
+    fn test_word_size_bits() {
+        assert_eq!(WordSize::bits(64), WordSize::U64);
+        assert_eq!(WordSize::bits(128), WordSize::U128);
+        assert_eq!(WordSize::default(), WordSize::U64);
+        assert_eq!(WordSize::bits(5).mask(0b11_0110), 0b1_0110);
+        assert_eq!(WordSize::U128.mask(Word::MAX), Word::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Word size must be between 1 and 128 bits!")]
+    fn test_word_size_bits_too_large() {
+        let _ = WordSize::bits(129);
+    }
+
+    #[test]
+    fn test_huge_shift_saturates_to_zero() {
+        let mut computer = Computer::try_from([
+            "Register A: 12345",
+            "Register B: 64",
+            "Register C: 0",
+            "",
+            "Program: 7,5,0,4",
+        ])
+        .unwrap();
+
+        assert_eq!(computer.run_until(&[], 10), StopReason::Halted);
+        assert_eq!(computer.registers(), Registers { a: 0, b: 64, c: 0 });
+    }
+
+    #[test]
+    fn test_with_word_size() {
+        let mut narrow = Computer::try_from([
+            "Register A: 255",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 1,7,5,5",
+        ])
+        .unwrap()
+        .with_word_size(WordSize::bits(2));
+
+        assert_eq!(narrow.registers().a, 3);
+        assert_eq!(narrow.execute_stack(), "3");
+        assert_eq!(narrow.registers().b, 3);
+
+        let program = assembler::assemble("adv 3\ncdv a\nbdv 1").unwrap();
+
+        let mut wide =
+            Computer::try_from_with_word_size([1 << 100, 0, 0], program, WordSize::U128).unwrap();
+
+        wide.execute_stack();
+
+        assert_eq!(
+            wide.registers(),
+            Registers {
+                a: 1 << 97,
+                b: 1 << 96,
+                c: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_computer_input_errors() {
+        let overflow = Computer::try_from([
+            "Register A: 0",
+            "Register B: 18446744073709551617",
+            "Register C: 0",
+            "",
+            "Program: 5,4",
+        ]);
+        let not_a_number = Computer::try_from([
+            "Register A: 0",
+            "Register B: 0",
+            "Register C: ten",
+            "",
+            "Program: 5,4",
+        ]);
+
+        assert_eq!(
+            overflow,
+            Err(ComputerInputError::RegisterOverflow {
+                line: 2,
+                value: 18_446_744_073_709_551_617,
+                bits: 64
+            })
+        );
+        assert_eq!(
+            overflow.unwrap_err().to_string(),
+            "line 2: 18446744073709551617 doesn't fit in 64 bits"
+        );
+        assert_eq!(
+            not_a_number,
+            Err(ComputerInputError::InvalidRegister {
+                line: 3,
+                value: String::from("ten")
+            })
+        );
+        assert_eq!(
+            Computer::try_from(["Register A: 0", "Program: 5,4"]),
+            Err(ComputerInputError::LineCount(2))
+        );
+    }
+
+    #[test]
+    fn test_try_from_with_word_size() {
+        let wide = Computer::try_from_with_word_size([0, 0, Word::MAX], vec![5, 6], WordSize::U128);
+        let narrow = Computer::try_from_with_word_size([0, 0, 8], vec![5, 6], WordSize::bits(3));
+
+        assert_eq!(wide.unwrap().execute_stack(), "7");
+        assert_eq!(
+            narrow,
+            Err(ComputerInputError::RegisterOverflow {
+                line: 3,
+                value: 8,
+                bits: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_instructions_stop_execution() {
+        let mut reserved_combo = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,5,7,5,4",
+        ])
+        .unwrap();
+        let mut unknown_opcode = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,9,0",
+        ])
+        .unwrap();
+        let mut literal_too_large = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 1,8,5,5",
+        ])
+        .unwrap();
+        let mut odd_length = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,5",
+        ])
+        .unwrap();
+
+        assert_eq!(reserved_combo.execute_stack(), "2");
+        assert_eq!(
+            reserved_combo.run_until(&[], 10),
+            StopReason::InvalidInstruction(2)
+        );
+        assert_eq!(
+            unknown_opcode.run_until(&[], 10),
+            StopReason::InvalidInstruction(2)
+        );
+        assert_eq!(unknown_opcode.step(), None);
+        assert_eq!(
+            literal_too_large.run_until(&[], 10),
+            StopReason::InvalidInstruction(0)
+        );
+        assert_eq!(odd_length.run_until(&[], 10), StopReason::Halted);
+        assert_eq!(odd_length.output, vec![2]);
+    }
+
+    #[test]
+    fn test_invalid_instruction_at_cycle_limit() {
+        let mut computer = Computer::try_from([
+            "Register A: 10",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,5,7",
+        ])
+        .unwrap();
+
+        assert_eq!(computer.run_until(&[], 0), StopReason::CycleLimit);
+        assert_eq!(
+            computer.run_until(&[], 1),
+            StopReason::InvalidInstruction(2)
+        );
+        assert_eq!(
+            computer.run_until(&[], 1),
+            StopReason::InvalidInstruction(2)
         );
+        assert_eq!(computer.output, vec![2]);
     }
 }
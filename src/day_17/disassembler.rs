@@ -0,0 +1,227 @@
+use std::fmt;
+
+use super::computer::OpCode;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Problem {
+    UnknownOpCode,
+    MissingOperand,
+    OperandTooLarge,
+    ReservedComboOperand,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Problem::UnknownOpCode => "not an opcode",
+            Problem::MissingOperand => "missing operand",
+            Problem::OperandTooLarge => "operand is not a 3-bit value",
+            Problem::ReservedComboOperand => "combo operand 7 is reserved",
+        };
+
+        write!(f, "{message}")
+    }
+}
+
+/// One opcode and operand pair of a program, kept as the raw values so that broken programs
+/// can still be listed.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Instruction {
+    pub address: usize,
+    pub opcode_value: usize,
+    pub operand: Option<usize>,
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> Option<OpCode> {
+        OpCode::from_value(self.opcode_value)
+    }
+
+    pub fn problem(&self) -> Option<Problem> {
+        let Some(opcode) = self.opcode() else {
+            return Some(Problem::UnknownOpCode);
+        };
+
+        match self.operand {
+            None => Some(Problem::MissingOperand),
+            Some(operand) if operand > 7 => Some(Problem::OperandTooLarge),
+            Some(7) if opcode.uses_combo_operand() => Some(Problem::ReservedComboOperand),
+            Some(_) => None,
+        }
+    }
+
+    fn description(&self) -> String {
+        let (Some(opcode), Some(operand)) = (self.opcode(), self.operand) else {
+            return String::new();
+        };
+
+        let combo = Self::combo_description(operand);
+
+        match opcode {
+            OpCode::Adv => format!("A = A >> {combo}"),
+            OpCode::Bxl => format!("B = B ^ {operand}"),
+            OpCode::Bst => format!("B = {combo} % 8"),
+            OpCode::Jnz => format!("if A != 0 jump to {operand}"),
+            OpCode::Bxc => String::from("B = B ^ C"),
+            OpCode::Out => format!("output {combo} % 8"),
+            OpCode::Bdv => format!("B = A >> {combo}"),
+            OpCode::Cdv => format!("C = A >> {combo}"),
+        }
+    }
+
+    fn combo_description(operand: usize) -> String {
+        match operand {
+            0..=3 => format!("combo({operand})={operand}"),
+            4 => String::from("combo(4)=A"),
+            5 => String::from("combo(5)=B"),
+            6 => String::from("combo(6)=C"),
+            _ => format!("combo({operand})=?"),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.opcode() {
+            Some(opcode) => write!(f, "{}: {opcode}", self.address)?,
+            None => write!(f, "{}: {}", self.address, self.opcode_value)?,
+        }
+
+        if let Some(operand) = self.operand {
+            write!(f, " {operand}")?;
+        }
+
+        match self.problem() {
+            Some(problem) => write!(f, "  ; INVALID: {problem}"),
+            None => write!(f, "  ; {}", self.description()),
+        }
+    }
+}
+
+pub fn disassemble(program: &[usize]) -> Vec<Instruction> {
+    program
+        .chunks(2)
+        .enumerate()
+        .map(|(index, pair)| Instruction {
+            address: index * 2,
+            opcode_value: pair[0],
+            operand: pair.get(1).copied(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        let expected = vec![
+            Instruction {
+                address: 0,
+                opcode_value: 2,
+                operand: Some(4),
+            },
+            Instruction {
+                address: 2,
+                opcode_value: 1,
+                operand: Some(1),
+            },
+            Instruction {
+                address: 4,
+                opcode_value: 7,
+                operand: Some(5),
+            },
+        ];
+
+        let result = disassemble(&[2, 4, 1, 1, 7, 5]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_disassemble_odd_length() {
+        let expected = vec![
+            Instruction {
+                address: 0,
+                opcode_value: 5,
+                operand: Some(5),
+            },
+            Instruction {
+                address: 2,
+                opcode_value: 3,
+                operand: None,
+            },
+        ];
+
+        let result = disassemble(&[5, 5, 3]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_instruction_problem() {
+        let instruction = |opcode_value, operand| Instruction {
+            address: 0,
+            opcode_value,
+            operand,
+        };
+
+        assert_eq!(instruction(0, Some(6)).problem(), None);
+        assert_eq!(instruction(1, Some(7)).problem(), None);
+        assert_eq!(instruction(4, Some(7)).problem(), None);
+        assert_eq!(
+            instruction(5, Some(7)).problem(),
+            Some(Problem::ReservedComboOperand)
+        );
+        assert_eq!(
+            instruction(1, Some(8)).problem(),
+            Some(Problem::OperandTooLarge)
+        );
+        assert_eq!(
+            instruction(3, None).problem(),
+            Some(Problem::MissingOperand)
+        );
+        assert_eq!(
+            instruction(9, Some(0)).problem(),
+            Some(Problem::UnknownOpCode)
+        );
+    }
+
+    #[test]
+    fn test_instruction_to_string() {
+        let expected = [
+            "0: bst 4  ; B = combo(4)=A % 8",
+            "2: bxl 1  ; B = B ^ 1",
+            "4: cdv 5  ; C = A >> combo(5)=B",
+            "6: adv 3  ; A = A >> combo(3)=3",
+            "8: bxc 4  ; B = B ^ C",
+            "10: out 6  ; output combo(6)=C % 8",
+            "12: bdv 2  ; B = A >> combo(2)=2",
+            "14: jnz 0  ; if A != 0 jump to 0",
+        ];
+
+        let result: Vec<String> = disassemble(&[2, 4, 1, 1, 7, 5, 0, 3, 4, 4, 5, 6, 6, 2, 3, 0])
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_invalid_instruction_to_string() {
+        let expected = [
+            "0: out 7  ; INVALID: combo operand 7 is reserved",
+            "2: 9 1  ; INVALID: not an opcode",
+            "4: jnz  ; INVALID: missing operand",
+        ];
+
+        let result: Vec<String> = disassemble(&[5, 7, 9, 1, 3])
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+}
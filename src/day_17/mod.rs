@@ -1,8 +1,14 @@
+#[cfg(test)]
+mod assembler;
 mod computer;
+#[cfg(test)]
+mod disassembler;
+#[cfg(test)]
+mod symbolic;
 
 use crate::util::file_reader::to_string_vector;
 
-use computer::Computer;
+use computer::{Computer, Word, WordSize};
 
 pub fn run() {
     let input = to_string_vector("inputs/day_17.txt").expect("Something went wrong with Day 17!");
@@ -12,15 +18,21 @@ pub fn run() {
 }
 
 fn part_1(input: &[String]) -> String {
-    let mut computer = Computer::from(input);
+    let mut computer = Computer::try_from(input).expect("Day 17 input is not a computer!");
 
     computer.execute_stack()
 }
 
-fn part_2(input: &[String]) -> usize {
-    let computer = Computer::from(input);
+/// Searches with 128-bit registers, so values of register A too wide for the default 64 bits
+/// can still be found.
+fn part_2(input: &[String]) -> Word {
+    let computer = Computer::try_from(input)
+        .expect("Day 17 input is not a computer!")
+        .with_word_size(WordSize::U128);
 
-    computer.lowest_register_a_value_to_produce_program_copy()
+    computer
+        .lowest_register_a_value_to_produce_program_copy()
+        .expect("No value for register A makes a copy of the program!")
 }
 
 #[cfg(test)]
@@ -0,0 +1,618 @@
+use std::fmt;
+
+use super::computer::{OpCode, StopReason, Word};
+
+const SYMBOLIC_STEP_LIMIT: usize = 10_000;
+const MAX_SYMBOLIC_PATHS: usize = 10_000;
+const MAX_ENUMERATED_BITS: u32 = 24;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SymbolicError {
+    TooManyPaths,
+    /// Holds how many bits of register A the query would have to try every value of.
+    TooManyUnknowns(u32),
+}
+
+/// A value computed from the unknown initial value of register A.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expr {
+    A,
+    Const(Word),
+    Xor(Box<Expr>, Box<Expr>),
+    Shr(Box<Expr>, Box<Expr>),
+    Mod8(Box<Expr>),
+}
+
+impl Expr {
+    fn xor(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Const(left), Expr::Const(right)) => Expr::Const(left ^ right),
+            (Expr::Const(0), other) | (other, Expr::Const(0)) => other,
+            (left, right) => Expr::Xor(Box::new(left), Box::new(right)),
+        }
+    }
+
+    fn shr(value: Expr, amount: Expr, a_bits: u32) -> Expr {
+        match (value, amount) {
+            (value, Expr::Const(0)) => value,
+            (Expr::Const(value), Expr::Const(amount)) => Expr::Const(shift_right(value, amount)),
+            (value, Expr::Const(amount)) if shift_right(value.max_value(a_bits), amount) == 0 => {
+                Expr::Const(0)
+            }
+            (Expr::Shr(inner, first), Expr::Const(second)) => match *first {
+                Expr::Const(first) => {
+                    Expr::shr(*inner, Expr::Const(first.saturating_add(second)), a_bits)
+                }
+                first => Expr::Shr(
+                    Box::new(Expr::Shr(inner, Box::new(first))),
+                    Box::new(Expr::Const(second)),
+                ),
+            },
+            (value, amount) => Expr::Shr(Box::new(value), Box::new(amount)),
+        }
+    }
+
+    fn mod8(value: Expr, a_bits: u32) -> Expr {
+        match value {
+            Expr::Const(value) => Expr::Const(value % 8),
+            value if value.max_value(a_bits) < 8 => value,
+            value => Expr::Mod8(Box::new(value)),
+        }
+    }
+
+    /// Works out the value for a concrete register A.
+    pub fn evaluate(&self, a: Word) -> Word {
+        match self {
+            Expr::A => a,
+            Expr::Const(value) => *value,
+            Expr::Xor(left, right) => left.evaluate(a) ^ right.evaluate(a),
+            Expr::Shr(value, amount) => shift_right(value.evaluate(a), amount.evaluate(a)),
+            Expr::Mod8(value) => value.evaluate(a) % 8,
+        }
+    }
+
+    /// Returns a mask of the bits of register A the value depends on.
+    pub fn dependencies(&self, a_bits: u32) -> Word {
+        self.bit_dependencies(Word::MAX, a_bits)
+    }
+
+    /// Returns a mask of the bits of register A that the bits in `bits` of the value depend
+    /// on.
+    fn bit_dependencies(&self, bits: Word, a_bits: u32) -> Word {
+        if bits == 0 {
+            return 0;
+        }
+
+        match self {
+            Expr::A => bits & all_ones(a_bits),
+            Expr::Const(_) => 0,
+            Expr::Xor(left, right) => {
+                left.bit_dependencies(bits, a_bits) | right.bit_dependencies(bits, a_bits)
+            }
+            Expr::Shr(value, amount) => {
+                let largest_amount = amount.max_value(a_bits).min(Word::from(Word::BITS - 1));
+
+                (0..=largest_amount).fold(amount.dependencies(a_bits), |dependencies, shift| {
+                    dependencies | value.bit_dependencies(shift_left(bits, shift), a_bits)
+                })
+            }
+            Expr::Mod8(value) => value.bit_dependencies(bits & 7, a_bits),
+        }
+    }
+
+    fn max_value(&self, a_bits: u32) -> Word {
+        match self {
+            Expr::A => all_ones(a_bits),
+            Expr::Const(value) => *value,
+            Expr::Xor(left, right) => {
+                let largest = left.max_value(a_bits).max(right.max_value(a_bits));
+
+                Word::MAX.checked_shr(largest.leading_zeros()).unwrap_or(0)
+            }
+            Expr::Shr(value, amount) => match **amount {
+                Expr::Const(amount) => shift_right(value.max_value(a_bits), amount),
+                _ => value.max_value(a_bits),
+            },
+            Expr::Mod8(value) => value.max_value(a_bits).min(7),
+        }
+    }
+
+    /// Returns `n` if this is `A >> n`.
+    fn register_a_shift(&self) -> Option<Word> {
+        match self {
+            Expr::A => Some(0),
+            Expr::Shr(value, amount) => match (&**value, &**amount) {
+                (Expr::A, Expr::Const(amount)) => Some(*amount),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::A => write!(f, "a"),
+            Expr::Const(value) => write!(f, "{value}"),
+            Expr::Xor(left, right) => write!(f, "({left} ^ {right})"),
+            Expr::Shr(value, amount) => write!(f, "({value} >> {amount})"),
+            Expr::Mod8(value) => write!(f, "({value} % 8)"),
+        }
+    }
+}
+
+/// Something a path assumed about register A when it took a jump.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Constraint {
+    pub expr: Expr,
+    pub is_zero: bool,
+}
+
+impl Constraint {
+    pub fn holds(&self, a: Word) -> bool {
+        (self.expr.evaluate(a) == 0) == self.is_zero
+    }
+}
+
+/// One way through the program. Register A must be between `lowest_a` and `highest_a` and
+/// meet every constraint for the program to take this path.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Path {
+    pub outputs: Vec<Expr>,
+    pub constraints: Vec<Constraint>,
+    pub lowest_a: Word,
+    pub highest_a: Word,
+    pub stop_reason: StopReason,
+}
+
+impl Path {
+    pub fn admits(&self, a: Word) -> bool {
+        (self.lowest_a..=self.highest_a).contains(&a)
+            && self
+                .constraints
+                .iter()
+                .all(|constraint| constraint.holds(a))
+    }
+
+    fn decide(&self, expr: &Expr) -> Option<bool> {
+        if let Expr::Const(value) = expr {
+            return Some(*value == 0);
+        }
+
+        if let Some(shift) = expr.register_a_shift() {
+            let threshold = shift_left(1, shift);
+
+            if self.highest_a < threshold {
+                return Some(true);
+            }
+
+            if self.lowest_a >= threshold {
+                return Some(false);
+            }
+        }
+
+        self.constraints
+            .iter()
+            .find(|constraint| constraint.expr == *expr)
+            .map(|constraint| constraint.is_zero)
+    }
+
+    fn constrain(&mut self, expr: Expr, is_zero: bool) {
+        match expr.register_a_shift() {
+            Some(shift) if is_zero => {
+                self.highest_a = self.highest_a.min(shift_left(1, shift) - 1);
+            }
+            Some(shift) => self.lowest_a = self.lowest_a.max(shift_left(1, shift)),
+            None => self.constraints.push(Constraint { expr, is_zero }),
+        }
+    }
+}
+
+/// The register A values with `A & mask == value` that lie between `lowest_a` and
+/// `highest_a`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct RegisterACondition {
+    pub lowest_a: Word,
+    pub highest_a: Word,
+    pub mask: Word,
+    pub value: Word,
+}
+
+impl RegisterACondition {
+    pub fn matches(&self, a: Word) -> bool {
+        (self.lowest_a..=self.highest_a).contains(&a) && (a & self.mask == self.value)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SymbolicRun {
+    pub a_bits: u32,
+    pub paths: Vec<Path>,
+}
+
+impl SymbolicRun {
+    /// Returns the path the program takes for a concrete register A.
+    pub fn path_for(&self, a: Word) -> Option<&Path> {
+        self.paths.iter().find(|path| path.admits(a))
+    }
+
+    /// Describes every register A that makes output `index` equal `digit`, by trying all
+    /// values of the bits of A that the output and the path constraints depend on.
+    ///
+    /// # Errors
+    ///
+    /// If a path depends on more than [`MAX_ENUMERATED_BITS`] bits of A.
+    pub fn register_a_values_for_output(
+        &self,
+        index: usize,
+        digit: usize,
+    ) -> Result<Vec<RegisterACondition>, SymbolicError> {
+        let digit = Word::try_from(digit).expect("Digit doesn't fit in a word!");
+
+        let mut conditions = Vec::new();
+
+        for path in &self.paths {
+            let Some(output) = path.outputs.get(index) else {
+                continue;
+            };
+
+            let mask = path
+                .constraints
+                .iter()
+                .fold(output.dependencies(self.a_bits), |mask, constraint| {
+                    mask | constraint.expr.dependencies(self.a_bits)
+                });
+
+            if mask.count_ones() > MAX_ENUMERATED_BITS {
+                return Err(SymbolicError::TooManyUnknowns(mask.count_ones()));
+            }
+
+            let mut assignment: Word = 0;
+
+            loop {
+                if output.evaluate(assignment) == digit
+                    && path
+                        .constraints
+                        .iter()
+                        .all(|constraint| constraint.holds(assignment))
+                {
+                    conditions.push(RegisterACondition {
+                        lowest_a: path.lowest_a,
+                        highest_a: path.highest_a,
+                        mask,
+                        value: assignment,
+                    });
+                }
+
+                assignment = assignment.wrapping_sub(mask) & mask;
+
+                if assignment == 0 {
+                    break;
+                }
+            }
+        }
+
+        Ok(conditions)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    a: Expr,
+    b: Expr,
+    c: Expr,
+    pointer: usize,
+    steps: usize,
+    path: Path,
+}
+
+/// Runs `program` with the lowest `a_bits` bits of register A unknown, following both
+/// branches whenever a jump depends on them.
+///
+/// # Errors
+///
+/// If the program branches into more than [`MAX_SYMBOLIC_PATHS`] paths.
+pub fn execute(
+    program: &[usize],
+    register_b: Word,
+    register_c: Word,
+    a_bits: u32,
+) -> Result<SymbolicRun, SymbolicError> {
+    let mut paths = Vec::new();
+    let mut states = vec![State {
+        a: Expr::A,
+        b: Expr::Const(register_b),
+        c: Expr::Const(register_c),
+        pointer: 0,
+        steps: 0,
+        path: Path {
+            outputs: Vec::new(),
+            constraints: Vec::new(),
+            lowest_a: 0,
+            highest_a: all_ones(a_bits),
+            stop_reason: StopReason::Halted,
+        },
+    }];
+
+    while let Some(mut state) = states.pop() {
+        if paths.len() + states.len() >= MAX_SYMBOLIC_PATHS {
+            return Err(SymbolicError::TooManyPaths);
+        }
+
+        loop {
+            if state.pointer + 1 >= program.len() {
+                state.path.stop_reason = StopReason::Halted;
+
+                break;
+            }
+
+            if state.steps == SYMBOLIC_STEP_LIMIT {
+                state.path.stop_reason = StopReason::CycleLimit;
+
+                break;
+            }
+
+            let Some(step) = step(&mut state, program, a_bits) else {
+                state.path.stop_reason = StopReason::InvalidInstruction(state.pointer);
+
+                break;
+            };
+
+            state.steps += 1;
+
+            if let Step::Fork(target) = step {
+                let mut jumped = state.clone();
+
+                jumped.path.constrain(state.a.clone(), false);
+                jumped.pointer = target;
+                states.push(jumped);
+
+                state.path.constrain(state.a.clone(), true);
+                state.pointer += 2;
+            }
+        }
+
+        paths.push(state.path);
+    }
+
+    Ok(SymbolicRun { a_bits, paths })
+}
+
+enum Step {
+    Next,
+    /// A jump to the address that could go either way. The pointer is left for the caller to
+    /// move.
+    Fork(usize),
+}
+
+/// Executes the instruction at `state.pointer`, or returns `None` if it can't be executed.
+fn step(state: &mut State, program: &[usize], a_bits: u32) -> Option<Step> {
+    let opcode = OpCode::from_value(program[state.pointer])?;
+    let operand = program[state.pointer + 1];
+
+    let combo = |state: &State| match operand {
+        0..=3 => Some(Expr::Const(Word::try_from(operand).ok()?)),
+        4 => Some(state.a.clone()),
+        5 => Some(state.b.clone()),
+        6 => Some(state.c.clone()),
+        _ => None,
+    };
+
+    match opcode {
+        OpCode::Adv => state.a = Expr::shr(state.a.clone(), combo(state)?, a_bits),
+        OpCode::Bdv => state.b = Expr::shr(state.a.clone(), combo(state)?, a_bits),
+        OpCode::Cdv => state.c = Expr::shr(state.a.clone(), combo(state)?, a_bits),
+        OpCode::Bxl => {
+            let literal = Word::try_from(operand).ok().filter(|value| *value < 8)?;
+
+            state.b = Expr::xor(state.b.clone(), Expr::Const(literal));
+        }
+        OpCode::Bst => state.b = Expr::mod8(combo(state)?, a_bits),
+        OpCode::Bxc => state.b = Expr::xor(state.b.clone(), state.c.clone()),
+        OpCode::Out => state.path.outputs.push(Expr::mod8(combo(state)?, a_bits)),
+        OpCode::Jnz => match state.path.decide(&state.a) {
+            Some(true) => {}
+            Some(false) => {
+                state.pointer = operand;
+
+                return Some(Step::Next);
+            }
+            None => return Some(Step::Fork(operand)),
+        },
+    }
+
+    state.pointer += 2;
+
+    Some(Step::Next)
+}
+
+fn all_ones(bits: u32) -> Word {
+    Word::MAX.checked_shr(Word::BITS - bits).unwrap_or(0)
+}
+
+fn shift_right(value: Word, amount: Word) -> Word {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shr(amount))
+        .unwrap_or(0)
+}
+
+fn shift_left(value: Word, amount: Word) -> Word {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shl(amount))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day_17::computer::Computer;
+
+    const XOR_SHIFT_PROGRAM: &str = "
+        bst a
+        bxl 1
+        cdv b
+        adv 3
+        bxl 4
+        bxc
+        out b
+        jnz 0
+    ";
+
+    fn concrete_output(a: Word) -> String {
+        Computer::from_assembly([u64::try_from(a).unwrap(), 0, 0], XOR_SHIFT_PROGRAM)
+            .unwrap()
+            .execute_stack()
+    }
+
+    #[test]
+    fn test_run_symbolic() {
+        let run = Computer::from_assembly([0, 0, 0], "adv 3\nout a\njnz 0")
+            .unwrap()
+            .run_symbolic(9)
+            .unwrap();
+
+        let path = run.path_for(100).unwrap();
+
+        let outputs: Vec<String> = path.outputs.iter().map(ToString::to_string).collect();
+
+        assert_eq!(run.paths.len(), 3);
+        assert_eq!(outputs, ["((a >> 3) % 8)", "(a >> 6)", "0"]);
+        assert_eq!((path.lowest_a, path.highest_a), (64, 511));
+        assert!(path.constraints.is_empty());
+        assert_eq!(path.stop_reason, StopReason::Halted);
+    }
+
+    #[test]
+    fn test_run_symbolic_matches_concrete_run() {
+        let run = Computer::from_assembly([0, 0, 0], XOR_SHIFT_PROGRAM)
+            .unwrap()
+            .run_symbolic(12)
+            .unwrap();
+
+        for a in 0..(1 << 12) {
+            let path = run.path_for(a).unwrap();
+
+            let outputs: Vec<String> = path
+                .outputs
+                .iter()
+                .map(|output| output.evaluate(a).to_string())
+                .collect();
+
+            assert_eq!(outputs.join(","), concrete_output(a), "A = {a}");
+        }
+    }
+
+    #[test]
+    fn test_register_a_values_for_output() {
+        let run = Computer::from_assembly([0, 0, 0], XOR_SHIFT_PROGRAM)
+            .unwrap()
+            .run_symbolic(9)
+            .unwrap();
+
+        let second_outputs: Vec<Option<usize>> = (0..(1 << 9))
+            .map(|a| {
+                concrete_output(a)
+                    .split(',')
+                    .nth(1)
+                    .map(|output| output.parse().unwrap())
+            })
+            .collect();
+
+        for digit in 0..8 {
+            let conditions = run.register_a_values_for_output(1, digit).unwrap();
+
+            for (a, output) in (0..).zip(&second_outputs) {
+                let expected = *output == Some(digit);
+
+                let result = conditions.iter().any(|condition| condition.matches(a));
+
+                assert_eq!(result, expected, "A = {a}, digit = {digit}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_a_values_for_output_too_many_unknowns() {
+        let run = Computer::from_assembly([0, 0, 0], "cdv a\nout c")
+            .unwrap()
+            .run_symbolic(30)
+            .unwrap();
+
+        assert_eq!(
+            run.register_a_values_for_output(0, 0),
+            Err(SymbolicError::TooManyUnknowns(30))
+        );
+    }
+
+    #[test]
+    fn test_run_symbolic_stop_reasons() {
+        let invalid = Computer::try_from([
+            "Register A: 0",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,5,7",
+        ])
+        .unwrap()
+        .run_symbolic(8)
+        .unwrap();
+
+        let endless = Computer::from_assembly([0, 0, 0], "bxl 1\njnz 0")
+            .unwrap()
+            .run_symbolic(8)
+            .unwrap();
+
+        assert_eq!(invalid.paths.len(), 1);
+        assert_eq!(
+            invalid.paths[0].stop_reason,
+            StopReason::InvalidInstruction(2)
+        );
+        assert_eq!(
+            endless.path_for(0).map(|path| path.stop_reason),
+            Some(StopReason::Halted)
+        );
+        assert_eq!(
+            endless.path_for(1).map(|path| path.stop_reason),
+            Some(StopReason::CycleLimit)
+        );
+    }
+
+    #[test]
+    fn test_run_symbolic_rejects_bxl_8() {
+        let mut computer = Computer::try_from([
+            "Register A: 0",
+            "Register B: 0",
+            "Register C: 0",
+            "",
+            "Program: 5,4,1,8,5,5",
+        ])
+        .unwrap();
+
+        let run = computer.run_symbolic(8).unwrap();
+
+        assert_eq!(run.paths[0].stop_reason, StopReason::InvalidInstruction(2));
+        assert_eq!(run.paths[0].outputs.len(), 1);
+        assert_eq!(
+            computer.run_until(&[], 10),
+            StopReason::InvalidInstruction(2)
+        );
+    }
+
+    #[test]
+    fn test_expr_to_string() {
+        let expr = Expr::xor(
+            Expr::mod8(Expr::A, 8),
+            Expr::shr(Expr::A, Expr::Const(8), 8),
+        );
+
+        assert_eq!(expr.to_string(), "(a % 8)");
+        assert_eq!(
+            Expr::shr(Expr::shr(Expr::A, Expr::Const(2), 16), Expr::Const(3), 16).to_string(),
+            "(a >> 5)"
+        );
+    }
+}
@@ -2,7 +2,7 @@ mod records;
 
 use crate::util::file_reader::to_string_vector;
 
-use records::Record;
+use records::{Record, SafetyRules, UnsafeReason, Verdict};
 
 pub fn run() {
     let input = to_string_vector("inputs/day_2.txt").expect("Something went wrong with Day 2!");
@@ -11,6 +11,7 @@ pub fn run() {
 
     println!("Day 2 Part 1: {:?}", part_1(&records));
     println!("Day 2 Part 2: {:?}", part_2(&records));
+    println!("Day 2 Verdicts: {}", verdict_summary(&records));
 }
 
 fn part_1(records: &[Record]) -> usize {
@@ -24,8 +25,67 @@ fn part_2(records: &[Record]) -> usize {
         .count()
 }
 
+/// Counts the records by their verdict when one level may be removed, and how their first bad
+/// step breaks the rules.
+fn verdict_summary(records: &[Record]) -> String {
+    let rules = SafetyRules::default().with_max_removals(1);
+
+    let verdicts: Vec<Verdict> = records
+        .iter()
+        .map(|record| record.verdict(&rules))
+        .collect();
+
+    let safe = verdicts
+        .iter()
+        .filter(|verdict| matches!(verdict, Verdict::Safe))
+        .count();
+    let safe_with_removal = verdicts
+        .iter()
+        .filter(|verdict| matches!(verdict, Verdict::SafeWithRemovals { .. }))
+        .count();
+    let not_safe = verdicts.len() - safe - safe_with_removal;
+
+    let first_bad_steps: Vec<String> = [
+        UnsafeReason::DirectionChange,
+        UnsafeReason::ZeroStep,
+        UnsafeReason::StepTooSmall,
+        UnsafeReason::StepTooLarge,
+    ]
+    .into_iter()
+    .filter_map(|reason| {
+        let count = verdicts
+            .iter()
+            .filter(|verdict| match verdict {
+                Verdict::Safe => false,
+                Verdict::SafeWithRemovals { reason: found, .. }
+                | Verdict::Unsafe { reason: found, .. } => *found == reason,
+            })
+            .count();
+
+        (count > 0).then(|| format!("{reason} in {count}"))
+    })
+    .collect();
+
+    let mut summary =
+        format!("{safe} safe, {safe_with_removal} safe without one level, {not_safe} unsafe");
+
+    if !first_bad_steps.is_empty() {
+        summary.push_str("; the first bad step ");
+        summary.push_str(&first_bad_steps.join(", "));
+    }
+
+    summary
+}
+
 fn get_records(input: &[String]) -> Vec<Record> {
-    input.iter().map(|line| line.parse().unwrap()).collect()
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            line.parse()
+                .unwrap_or_else(|error| panic!("Invalid record on line {}, {error}!", index + 1))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -49,4 +109,21 @@ mod tests {
 
         assert_eq!(part_2(&records), 4);
     }
+
+    #[test]
+    fn test_verdict_summary() {
+        let input = to_string_vector("test_inputs/day_2.txt").unwrap();
+
+        let records = get_records(&input);
+
+        assert_eq!(
+            verdict_summary(&records),
+            "2 safe, 2 safe without one level, 2 unsafe; the first bad step changes direction in \
+             1, doesn't change in 1, changes too much in 2"
+        );
+        assert_eq!(
+            verdict_summary(&records[..1]),
+            "1 safe, 0 safe without one level, 0 unsafe"
+        );
+    }
 }
@@ -1,5 +1,133 @@
+use std::fmt;
 use std::str::FromStr;
-use std::string::ParseError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseRecordError {
+    level: String,
+}
+
+impl fmt::Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` isn't a level", self.level)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnsafeReason {
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+impl fmt::Display for UnsafeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnsafeReason::DirectionChange => write!(f, "changes direction"),
+            UnsafeReason::ZeroStep => write!(f, "doesn't change"),
+            UnsafeReason::StepTooSmall => write!(f, "changes too little"),
+            UnsafeReason::StepTooLarge => write!(f, "changes too much"),
+        }
+    }
+}
+
+/// Whether a record is safe, and why not. `index` is the level the first bad step leads to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Verdict {
+    Safe,
+    /// Holds the first bad step as in `Unsafe`, and the fewest levels, by index, that have to be
+    /// removed for the record to be safe.
+    SafeWithRemovals {
+        index: usize,
+        reason: UnsafeReason,
+        removed: Vec<usize>,
+    },
+    Unsafe {
+        index: usize,
+        reason: UnsafeReason,
+    },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Safe => write!(f, "safe"),
+            Verdict::SafeWithRemovals {
+                index,
+                reason,
+                removed,
+            } => {
+                let removed: Vec<String> = removed.iter().map(ToString::to_string).collect();
+
+                write!(
+                    f,
+                    "safe without levels {}, level {index} {reason}",
+                    removed.join(", ")
+                )
+            }
+            Verdict::Unsafe { index, reason } => write!(f, "unsafe, level {index} {reason}"),
+        }
+    }
+}
+
+/// What makes a record safe: every step between neighbouring levels is between `min_step`
+/// and `max_step` in the same direction, once up to `max_removals` levels are dropped.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SafetyRules {
+    pub min_step: u32,
+    pub max_step: u32,
+    pub max_removals: usize,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        SafetyRules {
+            min_step: 1,
+            max_step: 3,
+            max_removals: 0,
+        }
+    }
+}
+
+impl SafetyRules {
+    /// # Panics
+    ///
+    /// If `min_step` is greater than `max_step`, as no step could be valid.
+    #[cfg(test)]
+    pub fn with_steps(mut self, min_step: u32, max_step: u32) -> Self {
+        assert!(
+            min_step <= max_step,
+            "Min step {min_step} is greater than max step {max_step}!"
+        );
+
+        self.min_step = min_step;
+        self.max_step = max_step;
+
+        self
+    }
+
+    pub fn with_max_removals(mut self, max_removals: usize) -> Self {
+        self.max_removals = max_removals;
+
+        self
+    }
+
+    /// Steps of zero fit either direction, if `min_step` allows them.
+    fn is_valid_step(&self, from: u32, to: u32, increasing: bool) -> bool {
+        let step = from.abs_diff(to);
+
+        (self.min_step..=self.max_step).contains(&step) && (step == 0 || (from < to) == increasing)
+    }
+}
+
+/// How a safe run reaches a level in [`Record::removals_in_direction`].
+#[derive(Debug, Clone, Copy)]
+enum RunEnd {
+    /// The run starts at the level, after removing every level before it.
+    RunStart,
+    /// The run comes from the previous level in it, removing every level in between.
+    From(usize),
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Record {
@@ -8,58 +136,151 @@ pub struct Record {
 
 impl Record {
     pub fn is_safe(&self) -> bool {
-        Self::_is_safe(&self.levels)
+        self.is_safe_with(&SafetyRules::default())
     }
 
     pub fn is_safe_with_removal(&self) -> bool {
-        Self::_get_sub_one_level_combinations(&self.levels)
-            .into_iter()
-            .any(|levels| Self::_is_safe(&levels))
+        self.is_safe_with(&SafetyRules::default().with_max_removals(1))
     }
 
-    fn _is_safe(levels: &[u32]) -> bool {
-        let is_decreasing = levels.first().unwrap() > levels.last().unwrap();
+    pub fn is_safe_with(&self, rules: &SafetyRules) -> bool {
+        self.fewest_removals(rules).is_some()
+    }
 
-        for window in levels.windows(2) {
-            let previous = window.first().unwrap();
-            let current = window.last().unwrap();
+    /// Says whether the record is safe as it is, safe once some levels are removed, or why it's
+    /// unsafe before any are.
+    pub fn verdict(&self, rules: &SafetyRules) -> Verdict {
+        let Some((index, reason)) = self.first_unsafe_step(rules) else {
+            return Verdict::Safe;
+        };
 
-            let has_stopped_decreasing = (previous < current) && is_decreasing;
-            let has_stopped_increasing = (current < previous) && !is_decreasing;
-            let has_invalid_diff =
-                (3 < previous.abs_diff(*current)) || (previous.abs_diff(*current) == 0);
+        match self.fewest_removals(rules) {
+            Some(removed) => Verdict::SafeWithRemovals {
+                index,
+                reason,
+                removed,
+            },
+            None => Verdict::Unsafe { index, reason },
+        }
+    }
+
+    /// Finds the first step that breaks the rules. The first step that changes the level sets
+    /// the direction.
+    fn first_unsafe_step(&self, rules: &SafetyRules) -> Option<(usize, UnsafeReason)> {
+        let mut increasing = None;
+
+        self.levels
+            .windows(2)
+            .enumerate()
+            .find_map(|(index, pair)| {
+                let (from, to) = (pair[0], pair[1]);
+                let step = from.abs_diff(to);
+
+                let reason = if step == 0 && rules.min_step > 0 {
+                    Some(UnsafeReason::ZeroStep)
+                } else if step != 0 && *increasing.get_or_insert(from < to) != (from < to) {
+                    Some(UnsafeReason::DirectionChange)
+                } else if step < rules.min_step {
+                    Some(UnsafeReason::StepTooSmall)
+                } else if step > rules.max_step {
+                    Some(UnsafeReason::StepTooLarge)
+                } else {
+                    None
+                };
+
+                reason.map(|reason| (index + 1, reason))
+            })
+    }
 
-            if has_stopped_decreasing || has_stopped_increasing || has_invalid_diff {
-                return false;
+    /// The fewest levels that can be removed to make the record safe, trying increasing levels
+    /// first on a tie, or `None` if it takes more than `max_removals`.
+    fn fewest_removals(&self, rules: &SafetyRules) -> Option<Vec<usize>> {
+        let [increasing, decreasing] =
+            [true, false].map(|increasing| self.removals_in_direction(rules, increasing));
+
+        match (increasing, decreasing) {
+            (Some(increasing), Some(decreasing)) if decreasing.len() < increasing.len() => {
+                Some(decreasing)
             }
+            (Some(removed), _) | (None, Some(removed)) => Some(removed),
+            (None, None) => None,
         }
-
-        true
     }
 
-    fn _get_sub_one_level_combinations(levels: &[u32]) -> Vec<Vec<u32>> {
-        let mut result = vec![levels.to_vec()];
+    /// Goes through the levels once, tracking for each level and number of removals so far
+    /// whether a safe run can end at that level, and the level before it in the run. A run can
+    /// only come from the `max_removals + 1` levels before it, so this takes
+    /// `O(n * max_removals^2)` time.
+    fn removals_in_direction(&self, rules: &SafetyRules, increasing: bool) -> Option<Vec<usize>> {
+        let len = self.levels.len();
+
+        if len == 0 {
+            return Some(Vec::new());
+        }
+
+        // There are never more than `len` levels to remove.
+        let max_removals = rules.max_removals.min(len);
+
+        // `run_ends[index][removals]` says how a run can end there, if it can.
+        let mut run_ends: Vec<Vec<Option<RunEnd>>> = vec![vec![None; max_removals + 1]; len];
+
+        for (index, level) in self.levels.iter().enumerate() {
+            if index <= max_removals {
+                run_ends[index][index] = Some(RunEnd::RunStart);
+            }
 
-        for index in 0..levels.len() {
-            let mut temp = levels.to_vec();
+            for skipped in 0..index.min(max_removals + 1) {
+                let previous = index - 1 - skipped;
 
-            temp.remove(index);
+                if !rules.is_valid_step(self.levels[previous], *level, increasing) {
+                    continue;
+                }
 
-            result.push(temp);
+                for removals in 0..=(max_removals - skipped) {
+                    if run_ends[previous][removals].is_some()
+                        && run_ends[index][removals + skipped].is_none()
+                    {
+                        run_ends[index][removals + skipped] = Some(RunEnd::From(previous));
+                    }
+                }
+            }
+        }
+
+        let (mut index, mut removals) = (0..len)
+            .flat_map(|index| (0..=max_removals).map(move |removals| (index, removals)))
+            .filter(|(index, removals)| run_ends[*index][*removals].is_some())
+            .map(|(index, removals)| (removals + len - 1 - index, index, removals))
+            .filter(|(total, _, _)| *total <= max_removals)
+            .min()
+            .map(|(_, index, removals)| (index, removals))?;
+
+        let mut kept = vec![false; len];
+
+        while let Some(RunEnd::From(previous)) = run_ends[index][removals] {
+            kept[index] = true;
+            removals -= index - previous - 1;
+            index = previous;
         }
 
-        result
+        kept[index] = true;
+
+        Some((0..len).filter(|index| !kept[*index]).collect())
     }
 }
 
 impl FromStr for Record {
-    type Err = ParseError;
+    type Err = ParseRecordError;
 
+    /// An empty line is a record without levels.
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let levels = input
-            .split(' ')
-            .map(|str_val| str_val.parse().unwrap())
-            .collect();
+            .split_whitespace()
+            .map(|level| {
+                level.parse().map_err(|_| ParseRecordError {
+                    level: String::from(level),
+                })
+            })
+            .collect::<Result<_, _>>()?;
 
         Ok(Record { levels })
     }
@@ -82,6 +303,20 @@ mod tests {
         assert_eq!(result, Ok(expected));
     }
 
+    #[test]
+    fn test_from_str_errors() {
+        let expected = Err(ParseRecordError {
+            level: String::from("x"),
+        });
+
+        assert_eq!("7 x 4".parse::<Record>(), expected);
+        assert_eq!(
+            "7 -6".parse::<Record>().unwrap_err().to_string(),
+            "`-6` isn't a level"
+        );
+        assert_eq!(" 7  6 ".parse(), Ok(Record { levels: vec![7, 6] }));
+    }
+
     #[test]
     fn test_is_safe() {
         let records: Vec<Record> = ["7 6 4 2 1", "1 3 6 7 9"]
@@ -117,20 +352,252 @@ mod tests {
     }
 
     #[test]
-    fn test_get_sub_one_level_combinations() {
-        let input = [7, 6, 4, 2, 1];
+    fn test_is_safe_with_steps() {
+        let record: Record = "1 1 3 7 8".parse().unwrap();
+
+        assert!(!record.is_safe_with(&SafetyRules::default()));
+        assert!(record.is_safe_with(&SafetyRules::default().with_steps(0, 4)));
+        assert!(!record.is_safe_with(&SafetyRules::default().with_steps(1, 4)));
+        assert!(record.is_safe_with(&SafetyRules::default().with_steps(1, 4).with_max_removals(1)));
+        assert!(!record.is_safe_with(&SafetyRules::default().with_steps(2, 4).with_max_removals(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Min step 4 is greater than max step 2!")]
+    fn test_with_steps_rejects_empty_range() {
+        let _ = SafetyRules::default().with_steps(4, 2);
+    }
+
+    #[test]
+    fn test_is_safe_with_max_removals() {
+        let record: Record = "1 9 2 3 9 4".parse().unwrap();
+
+        let result: Vec<bool> = (0..4)
+            .map(|max_removals| {
+                record.is_safe_with(&SafetyRules::default().with_max_removals(max_removals))
+            })
+            .collect();
+
+        assert_eq!(result, [false, false, true, true]);
+    }
+
+    #[test]
+    fn test_is_safe_with_unlimited_removals() {
+        let rules = SafetyRules::default().with_max_removals(usize::MAX);
+
+        let records: Vec<Record> = ["1 9 2 3 9 4", "5 5 5 5", "9 1 9 1 9"]
+            .into_iter()
+            .map(|input| input.parse::<Record>().unwrap())
+            .collect();
+
+        assert!(records.iter().all(|record| record.is_safe_with(&rules)));
+    }
+
+    #[test]
+    fn test_is_safe_short_records() {
+        let records: Vec<Record> = ["", "5", "5 5", "5 5 5"]
+            .into_iter()
+            .map(|input| input.parse::<Record>().unwrap())
+            .collect();
+
+        let result: Vec<(bool, bool)> = records
+            .iter()
+            .map(|record| (record.is_safe(), record.is_safe_with_removal()))
+            .collect();
+
+        assert_eq!(
+            result,
+            [(true, true), (true, true), (false, true), (false, false)]
+        );
+    }
+
+    /// Whether the levels are safe once the levels in the `removed` bitmask are dropped.
+    fn is_safe_without(levels: &[u32], removed: u32, rules: &SafetyRules) -> bool {
+        let kept: Vec<u32> = (0..levels.len())
+            .filter(|index| removed & (1 << index) == 0)
+            .map(|index| levels[index])
+            .collect();
+
+        [true, false].into_iter().any(|increasing| {
+            kept.windows(2)
+                .all(|pair| rules.is_valid_step(pair[0], pair[1], increasing))
+        })
+    }
+
+    /// 300 records of 6 levels from 0-4, from an xorshift generator.
+    fn random_levels() -> Vec<Vec<u32>> {
+        let mut state: u32 = 2024;
+
+        (0..300)
+            .map(|_| {
+                (0..6)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 17;
+                        state ^= state << 5;
+
+                        state % 5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_is_safe_with_matches_removing_every_subset() {
+        for levels in random_levels() {
+            let record = Record {
+                levels: levels.clone(),
+            };
+
+            for max_removals in 0..3 {
+                let rules = SafetyRules::default().with_max_removals(max_removals);
+
+                let expected = (0_u32..(1 << levels.len()))
+                    .filter(|removed| removed.count_ones() as usize <= max_removals)
+                    .any(|removed| is_safe_without(&levels, removed, &rules));
+
+                assert_eq!(
+                    record.is_safe_with(&rules),
+                    expected,
+                    "{levels:?} with {max_removals} removals"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fewest_removals_is_a_minimum_set() {
+        for levels in random_levels() {
+            let record = Record {
+                levels: levels.clone(),
+            };
+
+            for max_removals in 0..4 {
+                let rules = SafetyRules::default().with_max_removals(max_removals);
+
+                let expected = (0_u32..(1 << levels.len()))
+                    .filter(|removed| is_safe_without(&levels, *removed, &rules))
+                    .map(|removed| removed.count_ones() as usize)
+                    .min()
+                    .filter(|fewest| *fewest <= max_removals);
+
+                let result = record.fewest_removals(&rules);
+
+                assert_eq!(
+                    result.as_ref().map(Vec::len),
+                    expected,
+                    "{levels:?} with {max_removals} removals"
+                );
+
+                if let Some(removed) = result {
+                    let removed = removed.iter().fold(0, |mask, index| mask | (1 << index));
+
+                    assert!(is_safe_without(&levels, removed, &rules), "{levels:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_verdict() {
+        let records: Vec<Record> = [
+            "7 6 4 2 1",
+            "1 2 7 8 9",
+            "9 7 6 2 1",
+            "1 3 2 4 5",
+            "8 6 4 4 1",
+            "1 3 6 7 9",
+        ]
+        .into_iter()
+        .map(|input| input.parse::<Record>().unwrap())
+        .collect();
+
+        let unsafe_at = |index, reason| Verdict::Unsafe { index, reason };
+
+        let expected = [
+            Verdict::Safe,
+            unsafe_at(2, UnsafeReason::StepTooLarge),
+            unsafe_at(3, UnsafeReason::StepTooLarge),
+            unsafe_at(2, UnsafeReason::DirectionChange),
+            unsafe_at(3, UnsafeReason::ZeroStep),
+            Verdict::Safe,
+        ];
+
+        let result: Vec<Verdict> = records
+            .iter()
+            .map(|record| record.verdict(&SafetyRules::default()))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_verdict_with_removal() {
+        let records: Vec<Record> = ["1 2 7 8 9", "1 3 2 4 5", "8 6 4 4 1"]
+            .into_iter()
+            .map(|input| input.parse::<Record>().unwrap())
+            .collect();
 
-        let expected = vec![
-            vec![7, 6, 4, 2, 1],
-            vec![6, 4, 2, 1],
-            vec![7, 4, 2, 1],
-            vec![7, 6, 2, 1],
-            vec![7, 6, 4, 1],
-            vec![7, 6, 4, 2],
+        let expected = [
+            Verdict::Unsafe {
+                index: 2,
+                reason: UnsafeReason::StepTooLarge,
+            },
+            Verdict::SafeWithRemovals {
+                index: 2,
+                reason: UnsafeReason::DirectionChange,
+                removed: vec![1],
+            },
+            Verdict::SafeWithRemovals {
+                index: 3,
+                reason: UnsafeReason::ZeroStep,
+                removed: vec![2],
+            },
         ];
 
-        let result = Record::_get_sub_one_level_combinations(&input);
+        let rules = SafetyRules::default().with_max_removals(1);
+
+        let result: Vec<Verdict> = records
+            .iter()
+            .map(|record| record.verdict(&rules))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(expected[0].to_string(), "unsafe, level 2 changes too much");
+    }
+
+    #[test]
+    fn test_verdict_with_several_removals() {
+        let record: Record = "1 9 2 3 9 4".parse().unwrap();
+
+        let result = record.verdict(&SafetyRules::default().with_max_removals(2));
+
+        let expected = Verdict::SafeWithRemovals {
+            index: 1,
+            reason: UnsafeReason::StepTooLarge,
+            removed: vec![1, 4],
+        };
 
         assert_eq!(result, expected);
+        assert_eq!(
+            result.to_string(),
+            "safe without levels 1, 4, level 1 changes too much"
+        );
+    }
+
+    #[test]
+    fn test_verdict_step_too_small() {
+        let record: Record = "1 3 4 6".parse().unwrap();
+
+        let expected = Verdict::Unsafe {
+            index: 2,
+            reason: UnsafeReason::StepTooSmall,
+        };
+
+        assert_eq!(
+            record.verdict(&SafetyRules::default().with_steps(2, 3)),
+            expected
+        );
     }
 }
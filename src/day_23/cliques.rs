@@ -0,0 +1,244 @@
+use crate::util::graph::NodeTable;
+
+/// The network with every computer replaced by its position in the sorted list of ids, and
+/// each computer only storing the connections to computers after it.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct InternedNetwork {
+    ids: NodeTable,
+    later_neighbours: Vec<Vec<usize>>,
+}
+
+impl InternedNetwork {
+    /// Builds the network from each computer's id and the ids it's connected to.
+    pub fn new<'a, I, J>(adjacency: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, J)>,
+        J: IntoIterator<Item = &'a str>,
+    {
+        let adjacency: Vec<(&str, J)> = adjacency.into_iter().collect();
+
+        let mut sorted_ids: Vec<&str> = adjacency.iter().map(|(id, _)| *id).collect();
+
+        sorted_ids.sort_unstable();
+
+        // Interning the ids in sorted order gives them indices in the same order.
+        let mut ids = NodeTable::new();
+
+        for id in sorted_ids {
+            ids.intern(id);
+        }
+
+        let mut later_neighbours = vec![Vec::new(); ids.len()];
+
+        for (id, neighbours) in adjacency {
+            let index = ids.index(id).expect("Every id was interned!");
+
+            later_neighbours[index].extend(
+                neighbours
+                    .into_iter()
+                    .filter_map(|neighbour| ids.index(neighbour))
+                    .filter(|neighbour| *neighbour > index),
+            );
+        }
+
+        for neighbours in &mut later_neighbours {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+
+        InternedNetwork {
+            ids,
+            later_neighbours,
+        }
+    }
+
+    fn is_connected(&self, index_a: usize, index_b: usize) -> bool {
+        let (lower, higher) = (index_a.min(index_b), index_a.max(index_b));
+
+        self.later_neighbours[lower].binary_search(&higher).is_ok()
+    }
+
+    /// Counts the sets of `size` computers that are all connected to each other, without
+    /// building any of them.
+    pub fn count_cliques_of_size(&self, size: usize) -> usize {
+        if size == 0 {
+            return 0;
+        }
+
+        (0..self.ids.len())
+            .map(|index| self.count_extensions(&self.later_neighbours[index], size - 1))
+            .sum()
+    }
+
+    /// Counts the ways of picking `remaining` connected computers from `candidates`.
+    fn count_extensions(&self, candidates: &[usize], remaining: usize) -> usize {
+        if remaining == 0 {
+            return 1;
+        }
+
+        if candidates.len() < remaining {
+            return 0;
+        }
+
+        candidates
+            .iter()
+            .enumerate()
+            .map(|(position, candidate)| {
+                let next: Vec<usize> = candidates[(position + 1)..]
+                    .iter()
+                    .copied()
+                    .filter(|other| self.is_connected(*candidate, *other))
+                    .collect();
+
+                self.count_extensions(&next, remaining - 1)
+            })
+            .sum()
+    }
+
+    pub fn cliques_of_size(&self, size: usize) -> CliquesOfSize<'_> {
+        let stack = if size == 0 {
+            Vec::new()
+        } else {
+            vec![((0..self.ids.len()).collect(), 0)]
+        };
+
+        CliquesOfSize {
+            network: self,
+            size,
+            clique: Vec::new(),
+            stack,
+        }
+    }
+}
+
+/// Yields every set of `size` connected computers exactly once, as ids in sorted order.
+///
+/// A clique is only ever built up from its lowest index, one higher index at a time, so no
+/// clique can be reached in two orders.
+#[derive(Debug)]
+pub struct CliquesOfSize<'a> {
+    network: &'a InternedNetwork,
+    size: usize,
+    clique: Vec<usize>,
+    /// For each computer in `clique`, and one more for the empty clique at the bottom, the
+    /// computers connected to the whole clique so far and how many of them have been tried.
+    stack: Vec<(Vec<usize>, usize)>,
+}
+
+impl<'a> Iterator for CliquesOfSize<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (candidates, position) = self.stack.last_mut()?;
+
+            let Some(candidate) = candidates.get(*position).copied() else {
+                self.stack.pop();
+                self.clique.pop();
+
+                continue;
+            };
+
+            *position += 1;
+
+            if self.clique.len() + 1 == self.size {
+                return Some(
+                    self.clique
+                        .iter()
+                        .chain([&candidate])
+                        .map(|index| self.network.ids.name(*index))
+                        .collect(),
+                );
+            }
+
+            let next: Vec<usize> = candidates[*position..]
+                .iter()
+                .copied()
+                .filter(|other| self.network.is_connected(candidate, *other))
+                .collect();
+
+            if self.clique.len() + 1 + next.len() >= self.size {
+                self.clique.push(candidate);
+                self.stack.push((next, 0));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(edges: &[(&str, &str)]) -> InternedNetwork {
+        InternedNetwork::new(edges.iter().flat_map(|(a, b)| [(*a, [*b]), (*b, [*a])]))
+    }
+
+    #[test]
+    fn test_interned_network() {
+        let mut ids = NodeTable::new();
+
+        for id in ["a", "b", "c"] {
+            ids.intern(id);
+        }
+
+        let expected = InternedNetwork {
+            ids,
+            later_neighbours: vec![vec![1, 2], vec![], vec![]],
+        };
+
+        let result = network(&[("c", "a"), ("a", "b"), ("b", "a")]);
+
+        assert_eq!(result, expected);
+        assert!(result.is_connected(1, 0));
+        assert!(!result.is_connected(1, 2));
+    }
+
+    #[test]
+    fn test_cliques_of_size() {
+        // Two triangles sharing the edge b-c, and a square c-d-e-f.
+        let network = network(&[
+            ("a", "b"),
+            ("a", "c"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "c"),
+        ]);
+
+        assert_eq!(network.count_cliques_of_size(0), 0);
+        assert_eq!(network.count_cliques_of_size(1), 6);
+        assert_eq!(network.count_cliques_of_size(2), 8);
+        assert_eq!(network.count_cliques_of_size(3), 2);
+        assert_eq!(network.count_cliques_of_size(4), 0);
+
+        let expected = vec![vec!["a", "b", "c"], vec!["b", "c", "d"]];
+
+        let result: Vec<Vec<&str>> = network.cliques_of_size(3).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_cliques_of_size_complete_graph() {
+        let ids = ["a", "b", "c", "d", "e"];
+
+        let edges: Vec<(&str, &str)> = ids
+            .iter()
+            .enumerate()
+            .flat_map(|(index, a)| ids[(index + 1)..].iter().map(move |b| (*a, *b)))
+            .collect();
+
+        let network = network(&edges);
+
+        let counts: Vec<usize> = (0..=6)
+            .map(|size| network.count_cliques_of_size(size))
+            .collect();
+
+        assert_eq!(counts, [0, 5, 10, 10, 5, 1, 0]);
+        assert_eq!(network.cliques_of_size(4).count(), 5);
+        assert_eq!(network.cliques_of_size(5).next(), Some(ids.to_vec()));
+        assert_eq!(network.cliques_of_size(0).next(), None);
+    }
+}
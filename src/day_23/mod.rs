@@ -1,3 +1,4 @@
+mod cliques;
 mod network;
 
 use crate::util::file_reader::to_string_vector;
@@ -9,16 +10,15 @@ pub fn run() {
 
     println!("Day 23 Part 1: {:?}", part_1(&input));
     println!("Day 23 Part 2: {}", part_2(&input));
+    println!("Day 23 Stats: {}", stats(&input));
 }
 
 fn part_1(input: &[String]) -> usize {
     let network = Network::from(input);
 
     network
-        .all_subnetworks_of_size(3)
-        .into_iter()
-        .map(|ids| ids.iter().filter(|id| id.starts_with('t')).count())
-        .filter(|count| *count > 0)
+        .cliques_of_size(3)
+        .filter(|ids| ids.iter().any(|id| id.starts_with('t')))
         .count()
 }
 
@@ -28,6 +28,32 @@ fn part_2(input: &[String]) -> String {
     network.longest_sub_network().join(",")
 }
 
+/// Summarises the shape of the network.
+fn stats(input: &[String]) -> String {
+    let network = Network::from(input);
+
+    let degrees = network.degree_distribution();
+    let connections = match (degrees.keys().next(), degrees.keys().last()) {
+        (Some(fewest), Some(most)) if fewest < most => format!("{fewest} to {most}"),
+        (Some(fewest), _) => fewest.to_string(),
+        _ => String::from("0"),
+    };
+
+    let groups = match network.connected_components().len() {
+        1 => String::from("one group"),
+        count => format!("{count} groups"),
+    };
+
+    format!(
+        "{} computers in {groups} with {connections} connections each, {} LAN parties of three, \
+         {} maximal LAN parties, average clustering {:.3}",
+        degrees.values().sum::<usize>(),
+        network.count_cliques_of_size(3),
+        network.maximal_cliques().len(),
+        network.average_clustering_coefficient()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +71,23 @@ mod tests {
 
         assert_eq!(part_2(&input), "co,de,ka,ta");
     }
+
+    #[test]
+    fn test_stats() {
+        let input = to_string_vector("test_inputs/day_23.txt").unwrap();
+
+        assert_eq!(
+            stats(&input),
+            "16 computers in one group with 4 connections each, 12 LAN parties of three, 15 \
+             maximal LAN parties, average clustering 0.375"
+        );
+
+        let split = ["a-b", "b-c", "d-e"].map(String::from);
+
+        assert_eq!(
+            stats(&split),
+            "5 computers in 2 groups with 1 to 2 connections each, 0 LAN parties of three, 3 \
+             maximal LAN parties, average clustering 0.000"
+        );
+    }
 }
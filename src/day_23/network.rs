@@ -1,4 +1,14 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#[cfg(test)]
+use std::fmt::Write;
+
+#[cfg(test)]
+use crate::util::graph::dot_quoted;
+
+use super::cliques::{CliquesOfSize, InternedNetwork};
 
 #[derive(Debug, PartialEq, Clone)]
 struct Computer {
@@ -18,114 +28,407 @@ impl Computer {
         self.ids_connected_to.insert(other_id.to_string())
     }
 
-    fn is_connected_to_all(&self, ids: &HashSet<String>) -> bool {
-        ids.is_subset(&self.ids_connected_to)
+    fn is_connected_to_all(&self, ids: &[&str]) -> bool {
+        ids.iter().all(|id| self.ids_connected_to.contains(*id))
     }
 }
 
-#[derive(Debug, Default, PartialEq, Clone)]
-struct SearchNode {
-    sub_network_ids: HashSet<String>,
-    ids_to_process: HashSet<String>,
+#[derive(Debug, Default, PartialEq)]
+pub struct Network {
+    map: HashMap<String, Computer>,
+    /// The same connections with integer ids for the clique searches, built once every
+    /// connection has been added.
+    interned: InternedNetwork,
 }
 
-impl SearchNode {
-    fn new(computer: &Computer) -> Self {
-        SearchNode {
-            sub_network_ids: HashSet::from([computer.id.clone()]),
-            ids_to_process: computer.ids_connected_to.clone(),
-        }
+impl Network {
+    /// Returns the largest set of computers that are all connected to each other, sorted. Ties
+    /// go to the set that sorts first.
+    pub fn longest_sub_network(&self) -> Vec<String> {
+        let mut longest: Vec<String> = Vec::new();
+
+        self.for_each_maximal_clique(|clique| {
+            let clique = Self::sorted_ids(clique);
+
+            // Longer cliques win, and cliques of the same length go to the one whose sorted
+            // ids come first.
+            let is_longer = match clique.len().cmp(&longest.len()) {
+                Ordering::Greater => true,
+                Ordering::Equal => clique < longest,
+                Ordering::Less => false,
+            };
+
+            if is_longer {
+                longest = clique;
+            }
+        });
+
+        longest
     }
 
-    fn len(&self) -> usize {
-        self.sub_network_ids.len()
+    /// Returns every set of connected computers that no other computer could join, each sorted.
+    pub fn maximal_cliques(&self) -> Vec<Vec<String>> {
+        let mut cliques = Vec::new();
+
+        self.for_each_maximal_clique(|clique| cliques.push(Self::sorted_ids(clique)));
+
+        cliques.sort();
+
+        cliques
     }
 
-    fn maximum_possible_len(&self) -> usize {
-        self.sub_network_ids.len() + self.ids_to_process.len()
+    /// Runs Bron–Kerbosch from every computer in degeneracy order, only looking at neighbours
+    /// later in the order, so each maximal clique is found exactly once.
+    fn for_each_maximal_clique<'a, F>(&'a self, mut on_clique: F)
+    where
+        F: FnMut(&[&'a str]),
+    {
+        let order = self.degeneracy_order();
+
+        let positions: HashMap<&str, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect();
+
+        for (position, id) in order.iter().enumerate() {
+            let (candidates, excluded) = self.map[*id]
+                .ids_connected_to
+                .iter()
+                .map(String::as_str)
+                .partition(|neighbour| positions[neighbour] > position);
+
+            self.bron_kerbosch(&mut vec![*id], candidates, excluded, &mut on_clique);
+        }
     }
 
-    fn clone_and_add_subnetwork_id_from(&self, computer: &Computer) -> Self {
-        let mut clone = self.clone();
+    /// Extends `clique` with every maximal choice of `candidates`. Computers in `excluded` are
+    /// connected to the whole clique too, but every clique they'd add has already been found.
+    fn bron_kerbosch<'a, F>(
+        &'a self,
+        clique: &mut Vec<&'a str>,
+        mut candidates: HashSet<&'a str>,
+        mut excluded: HashSet<&'a str>,
+        on_clique: &mut F,
+    ) where
+        F: FnMut(&[&'a str]),
+    {
+        // Any maximal clique either holds the pivot or a candidate the pivot isn't connected
+        // to, so only those candidates need a branch of their own.
+        let Some(pivot) = candidates.union(&excluded).copied().max_by_key(|id| {
+            let pivot = &self.map[*id];
+
+            candidates
+                .iter()
+                .filter(|candidate| pivot.is_connected_to_all(&[candidate]))
+                .count()
+        }) else {
+            on_clique(clique);
 
-        clone.sub_network_ids.insert(computer.id.clone());
+            return;
+        };
 
-        clone.ids_to_process = computer
-            .ids_connected_to
-            .difference(&clone.sub_network_ids)
-            .cloned()
+        let pivot = &self.map[pivot];
+
+        let branches: Vec<&str> = candidates
+            .iter()
+            .filter(|candidate| !pivot.is_connected_to_all(&[candidate]))
+            .copied()
             .collect();
 
-        clone
+        for id in branches {
+            clique.push(id);
+
+            let connected_to_clique = |ids: &HashSet<&'a str>| -> HashSet<&'a str> {
+                ids.iter()
+                    .filter(|other| self.map[**other].is_connected_to_all(clique))
+                    .copied()
+                    .collect()
+            };
+
+            let (next_candidates, next_excluded) = (
+                connected_to_clique(&candidates),
+                connected_to_clique(&excluded),
+            );
+
+            self.bron_kerbosch(clique, next_candidates, next_excluded, on_clique);
+            clique.pop();
+
+            candidates.remove(id);
+            excluded.insert(id);
+        }
+    }
+
+    /// Orders the computers by repeatedly taking the one with the fewest connections to the
+    /// computers not taken yet.
+    fn degeneracy_order(&self) -> Vec<&str> {
+        let mut degrees: HashMap<&str, usize> = self
+            .map
+            .iter()
+            .map(|(id, computer)| (id.as_str(), computer.ids_connected_to.len()))
+            .collect();
+
+        let mut queue: BTreeSet<(usize, &str)> =
+            degrees.iter().map(|(id, degree)| (*degree, *id)).collect();
+
+        let mut order = Vec::with_capacity(self.map.len());
+
+        while let Some((_, id)) = queue.pop_first() {
+            order.push(id);
+            degrees.remove(id);
+
+            for neighbour in &self.map[id].ids_connected_to {
+                if let Some(degree) = degrees.get_mut(neighbour.as_str()) {
+                    queue.remove(&(*degree, neighbour.as_str()));
+
+                    *degree -= 1;
+
+                    queue.insert((*degree, neighbour.as_str()));
+                }
+            }
+        }
+
+        order
     }
 
-    fn sub_network_ids_to_sorted_vec(&self) -> Vec<String> {
-        let mut as_vec: Vec<String> = self.sub_network_ids.iter().cloned().collect();
+    fn sorted_ids(ids: &[&str]) -> Vec<String> {
+        let mut sorted: Vec<String> = ids.iter().map(ToString::to_string).collect();
 
-        as_vec.sort();
+        sorted.sort();
 
-        as_vec
+        sorted
     }
-}
 
-#[derive(Debug, Default, PartialEq)]
-pub struct Network {
-    map: HashMap<String, Computer>,
-}
+    #[cfg(test)]
+    pub fn all_subnetworks_of_size(&self, size: usize) -> HashSet<Vec<String>> {
+        self.cliques_of_size(size)
+            .map(|ids| ids.into_iter().map(String::from).collect())
+            .collect()
+    }
 
-impl Network {
-    pub fn longest_sub_network(&self) -> Vec<String> {
-        let mut seen_sub_networks: HashSet<Vec<String>> = HashSet::new();
-        let mut processing: VecDeque<SearchNode> = self.map.values().map(SearchNode::new).collect();
+    /// Streams every set of `size` computers that are all connected to each other, once each
+    /// and with its ids sorted.
+    pub fn cliques_of_size(&self, size: usize) -> CliquesOfSize<'_> {
+        self.interned.cliques_of_size(size)
+    }
+
+    /// Counts the sets [`Network::cliques_of_size`] would stream, without building them.
+    pub fn count_cliques_of_size(&self, size: usize) -> usize {
+        self.interned.count_cliques_of_size(size)
+    }
 
-        let mut max_length_sub_network = Vec::new();
+    /// Maps each number of connections to how many computers have that many.
+    pub fn degree_distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
 
-        while let Some(node) = processing.pop_back() {
-            let impossible_to_exceed_maximum =
-                node.maximum_possible_len() <= max_length_sub_network.len();
+        for computer in self.map.values() {
+            *distribution
+                .entry(computer.ids_connected_to.len())
+                .or_insert(0) += 1;
+        }
 
-            let already_seen = !seen_sub_networks.insert(node.sub_network_ids_to_sorted_vec());
+        distribution
+    }
 
-            if already_seen || impossible_to_exceed_maximum {
+    /// Returns the groups of computers that can reach each other, each sorted, in order of
+    /// their lowest id.
+    pub fn connected_components(&self) -> Vec<Vec<String>> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut components = Vec::new();
+
+        for id in self.sorted_ids_in_network() {
+            if !seen.insert(id) {
                 continue;
             }
 
-            if node.len() > max_length_sub_network.len() {
-                max_length_sub_network = node.sub_network_ids_to_sorted_vec();
+            let mut component = vec![id.to_string()];
+            let mut to_visit = vec![id];
+
+            while let Some(id) = to_visit.pop() {
+                for neighbour in &self.map[id].ids_connected_to {
+                    if seen.insert(neighbour) {
+                        component.push(neighbour.clone());
+                        to_visit.push(neighbour);
+                    }
+                }
             }
 
-            node.ids_to_process
-                .iter()
-                .filter_map(|id| self.map.get(id))
-                .filter(|computer| computer.is_connected_to_all(&node.sub_network_ids))
-                .map(|computer| node.clone_and_add_subnetwork_id_from(computer))
-                .for_each(|new_node| processing.push_back(new_node));
+            component.sort();
+            components.push(component);
         }
 
-        max_length_sub_network
+        components
     }
 
-    pub fn all_subnetworks_of_size(&self, size: usize) -> HashSet<Vec<String>> {
-        let mut result = HashSet::new();
+    /// Counts, for each computer, the pairs of its neighbours that are connected to each other.
+    #[cfg(test)]
+    pub fn triangles_per_computer(&self) -> BTreeMap<String, usize> {
+        self.map
+            .keys()
+            .map(|id| (id.clone(), self.triangles_through(id)))
+            .collect()
+    }
+
+    fn triangles_through(&self, id: &str) -> usize {
+        let neighbours: Vec<&Computer> = self.map[id]
+            .ids_connected_to
+            .iter()
+            .map(|neighbour| &self.map[neighbour])
+            .collect();
+
+        neighbours
+            .iter()
+            .enumerate()
+            .map(|(index, computer)| {
+                neighbours[(index + 1)..]
+                    .iter()
+                    .filter(|other| computer.is_connected_to_all(&[&other.id]))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// The share of pairs of `id`'s neighbours that are connected to each other, or `None` if
+    /// `id` isn't in the network. Computers with fewer than two neighbours score 0.
+    pub fn clustering_coefficient(&self, id: &str) -> Option<f64> {
+        let degree = self.map.get(id)?.ids_connected_to.len();
 
-        let mut processing: VecDeque<SearchNode> = self.map.values().map(SearchNode::new).collect();
+        if degree < 2 {
+            return Some(0.0);
+        }
 
-        while let Some(node) = processing.pop_front() {
-            if node.len() == size {
-                result.insert(node.sub_network_ids_to_sorted_vec());
+        let pairs = degree * (degree - 1) / 2;
 
-                continue;
+        Some(count_as_f64(self.triangles_through(id)) / count_as_f64(pairs))
+    }
+
+    /// The mean clustering coefficient over every computer.
+    pub fn average_clustering_coefficient(&self) -> f64 {
+        if self.map.is_empty() {
+            return 0.0;
+        }
+
+        let total: f64 = self
+            .map
+            .keys()
+            .filter_map(|id| self.clustering_coefficient(id))
+            .sum();
+
+        total / count_as_f64(self.map.len())
+    }
+
+    /// Returns the ids along a path with the fewest hops from `from` to `to`, both included.
+    /// Ties go to the path that visits lower ids first.
+    #[cfg(test)]
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let (from, _) = self.map.get_key_value(from)?;
+
+        let mut previous: HashMap<&str, Option<&str>> = HashMap::from([(from.as_str(), None)]);
+        let mut queue = VecDeque::from([from.as_str()]);
+
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![id.to_string()];
+                let mut current = id;
+
+                while let Some(Some(before)) = previous.get(current) {
+                    path.push(before.to_string());
+                    current = before;
+                }
+
+                path.reverse();
+
+                return Some(path);
             }
 
-            node.ids_to_process
+            let mut neighbours: Vec<&str> = self.map[id]
+                .ids_connected_to
                 .iter()
-                .filter_map(|id| self.map.get(id))
-                .filter(|computer| computer.is_connected_to_all(&node.sub_network_ids))
-                .map(|computer| node.clone_and_add_subnetwork_id_from(computer))
-                .for_each(|new_node| processing.push_back(new_node));
+                .map(String::as_str)
+                .collect();
+
+            neighbours.sort_unstable();
+
+            for neighbour in neighbours {
+                if !previous.contains_key(neighbour) {
+                    previous.insert(neighbour, Some(id));
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Writes the network as an undirected Graphviz graph. Computers in `highlighted` are
+    /// filled in, and the connections between them drawn in bold red. Every id is quoted, so
+    /// ids with dashes, quotes or DOT keywords stay valid.
+    #[cfg(test)]
+    pub fn to_dot(&self, highlighted: &[String]) -> String {
+        let highlighted: HashSet<&str> = highlighted.iter().map(String::as_str).collect();
+
+        let mut dot = String::from("graph network {\n");
+
+        writeln!(dot, "    node [shape=circle, fontname=\"monospace\"];").unwrap();
+
+        let ids = self.sorted_ids_in_network();
+
+        if !highlighted.is_empty() {
+            dot.push('\n');
         }
 
-        result
+        for id in ids.iter().filter(|id| highlighted.contains(*id)) {
+            writeln!(
+                dot,
+                "    {} [style=filled, fillcolor=gold];",
+                dot_quoted(id)
+            )
+            .unwrap();
+        }
+
+        dot.push('\n');
+
+        for id in &ids {
+            let mut neighbours: Vec<&str> = self.map[*id]
+                .ids_connected_to
+                .iter()
+                .map(String::as_str)
+                .filter(|neighbour| neighbour > id)
+                .collect();
+
+            neighbours.sort_unstable();
+
+            for neighbour in neighbours {
+                let edge = format!("{} -- {}", dot_quoted(id), dot_quoted(neighbour));
+
+                if highlighted.contains(id) && highlighted.contains(neighbour) {
+                    writeln!(dot, "    {edge} [color=red, penwidth=2];").unwrap();
+                } else {
+                    writeln!(dot, "    {edge};").unwrap();
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    fn sorted_ids_in_network(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.map.keys().map(String::as_str).collect();
+
+        ids.sort_unstable();
+
+        ids
+    }
+
+    fn intern(&self) -> InternedNetwork {
+        InternedNetwork::new(self.map.values().map(|computer| {
+            (
+                computer.id.as_str(),
+                computer.ids_connected_to.iter().map(String::as_str),
+            )
+        }))
     }
 
     fn add_connection(&mut self, id_1: &str, id_2: &str) {
@@ -141,6 +444,12 @@ impl Network {
     }
 }
 
+/// Counts of computers and connections are far below 2^52, so they convert exactly.
+#[allow(clippy::cast_precision_loss)]
+fn count_as_f64(count: usize) -> f64 {
+    count as f64
+}
+
 impl<const N: usize> From<[&str; N]> for Network {
     fn from(input: [&str; N]) -> Self {
         let strings: Vec<String> = input.iter().map(ToString::to_string).collect();
@@ -164,6 +473,8 @@ impl From<&[String]> for Network {
             .filter_map(|line| line.split_once('-'))
             .for_each(|(lhs, rhs)| network.add_connection(lhs, rhs));
 
+        network.interned = network.intern();
+
         network
     }
 }
@@ -195,10 +506,13 @@ mod tests {
             (String::from("cg"), cg_computer),
         ]);
 
-        let expected = Network {
+        let mut expected = Network {
             map: expected_network_map,
+            ..Network::default()
         };
 
+        expected.interned = expected.intern();
+
         let result = Network::from(["kh-tc", "qp-kh", "de-cg"]);
 
         assert_eq!(result, expected);
@@ -243,6 +557,8 @@ mod tests {
         let result = network.all_subnetworks_of_size(3);
 
         assert_eq!(result.len(), expected.len());
+        assert_eq!(network.count_cliques_of_size(3), expected.len());
+        assert_eq!(network.cliques_of_size(3).count(), expected.len());
         for expected_subnetwork in expected {
             assert!(
                 result.contains(&expected_subnetwork),
@@ -250,4 +566,170 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_longest_sub_network_ties() {
+        let network = Network::from(["d-e", "e-f", "d-f", "a-b", "b-c", "a-c", "c-d"]);
+
+        assert_eq!(network.longest_sub_network(), ["a", "b", "c"]);
+        assert_eq!(
+            Network::default().longest_sub_network(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_maximal_cliques() {
+        let network = Network::from([
+            "kh-tc", "qp-kh", "de-cg", "ka-co", "yn-aq", "qp-ub", "cg-tb", "vc-aq", "tb-ka",
+            "wh-tc", "yn-cg", "kh-ub", "ta-co", "de-co", "tc-td", "tb-wq", "wh-td", "ta-ka",
+            "td-qp", "aq-cg", "wq-ub", "ub-vc", "de-ta", "wq-aq", "wq-vc", "wh-yn", "ka-de",
+            "kh-ta", "co-tc", "wh-qp", "tb-vc", "td-yn",
+        ]);
+
+        let expected: Vec<Vec<String>> = [
+            "aq,cg,yn",
+            "aq,vc,wq",
+            "cg,de",
+            "cg,tb",
+            "co,de,ka,ta",
+            "co,tc",
+            "ka,tb",
+            "kh,qp,ub",
+            "kh,ta",
+            "kh,tc",
+            "qp,td,wh",
+            "tb,vc,wq",
+            "tc,td,wh",
+            "td,wh,yn",
+            "ub,vc,wq",
+        ]
+        .iter()
+        .map(|ids| ids.split(',').map(String::from).collect())
+        .collect();
+
+        assert_eq!(network.maximal_cliques(), expected);
+    }
+
+    #[test]
+    fn test_degeneracy_order() {
+        // A triangle with a tail: every computer has at most two connections to computers
+        // after it in the order.
+        let network = Network::from(["a-b", "b-c", "a-c", "c-d", "d-e"]);
+
+        let order = network.degeneracy_order();
+
+        assert_eq!(order.len(), 5);
+        assert_eq!(order[0], "e");
+
+        for (position, id) in order.iter().enumerate() {
+            let later_neighbours = order[(position + 1)..]
+                .iter()
+                .filter(|other| network.map[*id].is_connected_to_all(&[other]))
+                .count();
+
+            assert!(later_neighbours <= 2, "{id} has {later_neighbours}");
+        }
+    }
+
+    #[test]
+    fn test_degree_distribution() {
+        let network = Network::from(["a-b", "b-c", "a-c", "c-d", "e-f"]);
+
+        let expected = BTreeMap::from([(1, 3), (2, 2), (3, 1)]);
+
+        assert_eq!(network.degree_distribution(), expected);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let network = Network::from(["e-f", "b-c", "a-c", "c-d", "g-e"]);
+
+        let expected = vec![
+            vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+                String::from("d"),
+            ],
+            vec![String::from("e"), String::from("f"), String::from("g")],
+        ];
+
+        assert_eq!(network.connected_components(), expected);
+    }
+
+    #[test]
+    fn test_triangles_and_clustering() {
+        let network = Network::from(["a-b", "b-c", "a-c", "c-d"]);
+
+        let expected = BTreeMap::from([
+            (String::from("a"), 1),
+            (String::from("b"), 1),
+            (String::from("c"), 1),
+            (String::from("d"), 0),
+        ]);
+
+        assert_eq!(network.triangles_per_computer(), expected);
+        assert_eq!(network.clustering_coefficient("a"), Some(1.0));
+        assert_eq!(network.clustering_coefficient("c"), Some(1.0 / 3.0));
+        assert_eq!(network.clustering_coefficient("d"), Some(0.0));
+        assert_eq!(network.clustering_coefficient("z"), None);
+        assert!((network.average_clustering_coefficient() - 7.0 / 12.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let network = Network::from([
+            "kh-tc", "qp-kh", "de-cg", "ka-co", "yn-aq", "qp-ub", "cg-tb", "vc-aq", "tb-ka",
+            "wh-tc", "yn-cg", "kh-ub", "ta-co", "de-co", "tc-td", "tb-wq", "wh-td", "ta-ka",
+            "td-qp", "aq-cg", "wq-ub", "ub-vc", "de-ta", "wq-aq", "wq-vc", "wh-yn", "ka-de",
+            "kh-ta", "co-tc", "wh-qp", "tb-vc", "td-yn",
+        ]);
+
+        let expected = ["kh", "ta", "de", "cg"].map(String::from).to_vec();
+
+        assert_eq!(network.shortest_path("kh", "cg"), Some(expected));
+        assert_eq!(
+            network.shortest_path("kh", "kh"),
+            Some(vec![String::from("kh")])
+        );
+        assert_eq!(network.shortest_path("kh", "zz"), None);
+        assert_eq!(Network::from(["a-b", "c-d"]).shortest_path("a", "d"), None);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let network = Network::from(["a-b", "b-c", "a-c", "c-d"]);
+
+        let expected = r#"graph network {
+    node [shape=circle, fontname="monospace"];
+
+    "a" [style=filled, fillcolor=gold];
+    "b" [style=filled, fillcolor=gold];
+
+    "a" -- "b" [color=red, penwidth=2];
+    "a" -- "c";
+    "b" -- "c";
+    "c" -- "d";
+}
+"#;
+
+        assert_eq!(
+            network.to_dot(&[String::from("b"), String::from("a")]),
+            expected
+        );
+        assert!(!network.to_dot(&[]).contains("gold"));
+    }
+
+    #[test]
+    fn test_to_dot_awkward_ids() {
+        let network = Network::from(["node-web-01", "a\"b-graph", "a\"b-node"]);
+
+        let dot = network.to_dot(&[String::from("node")]);
+
+        assert!(dot.contains(r#"    "node" [style=filled, fillcolor=gold];"#));
+        assert!(dot.contains(r#"    "node" -- "web-01";"#));
+        assert!(dot.contains(r#"    "a\"b" -- "graph";"#));
+        assert!(dot.contains(r#"    "a\"b" -- "node";"#));
+    }
 }
@@ -1,12 +1,38 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::util::graph::{self, GraphError};
+
+#[cfg(test)]
+use super::export;
+use super::swap_search;
+#[cfg(test)]
+use super::truth_table::TruthTable;
+use super::truth_table::{self, EquivalenceReport, TruthTableError};
+
+pub const ADDER_TEST_RANDOM_PAIRS: usize = 100;
+/// Any seed works, this one just keeps the adder's test pairs the same between runs.
+pub const ADDER_TEST_SEED: u64 = 24;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum Operation {
+pub enum Operation {
     And,
     Xor,
     Or,
 }
 
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::And => "AND",
+            Operation::Xor => "XOR",
+            Operation::Or => "OR",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
 impl From<&str> for Operation {
     fn from(input: &str) -> Self {
         match input {
@@ -19,11 +45,11 @@ impl From<&str> for Operation {
 }
 
 #[derive(Debug, PartialEq, Clone)]
-struct Gate {
-    input_wire_id_a: String,
-    input_wire_id_b: String,
-    output_wire_id: String,
-    operation: Operation,
+pub struct Gate {
+    pub input_wire_id_a: String,
+    pub input_wire_id_b: String,
+    pub output_wire_id: String,
+    pub operation: Operation,
 }
 
 impl Gate {
@@ -36,22 +62,13 @@ impl Gate {
         }
     }
 
-    fn apply(&self, input_wire_state_a: bool, input_wire_state_b: bool) -> bool {
+    pub fn apply(&self, input_wire_state_a: bool, input_wire_state_b: bool) -> bool {
         match self.operation {
             Operation::And => input_wire_state_a & input_wire_state_b,
             Operation::Xor => input_wire_state_a ^ input_wire_state_b,
             Operation::Or => input_wire_state_a | input_wire_state_b,
         }
     }
-
-    fn has_input_id(&self, id: &str) -> bool {
-        self.input_wire_id_a == id || self.input_wire_id_b == id
-    }
-
-    fn has_xy_inputs(&self) -> bool {
-        (self.input_wire_id_a.starts_with('x') || self.input_wire_id_a.starts_with('y'))
-            && (self.input_wire_id_b.starts_with('x') || self.input_wire_id_b.starts_with('y'))
-    }
 }
 
 impl From<&String> for Gate {
@@ -90,23 +107,184 @@ impl Wire {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CircuitError {
+    /// Holds the wires around the loop, starting and ending with the same wire.
+    Cycle(Vec<String>),
+    /// Holds the wires that gates read but that are neither inputs nor gate outputs.
+    UndrivenWires(Vec<String>),
+    /// Holds a wire that more than one gate writes to.
+    MultipleDrivers(String),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Cycle(wires) => write!(f, "wires form a cycle: {}", wires.join(" -> ")),
+            CircuitError::UndrivenWires(wires) => {
+                write!(f, "wires have no value: {}", wires.join(", "))
+            }
+            CircuitError::MultipleDrivers(wire) => {
+                write!(f, "wire {wire} is the output of more than one gate")
+            }
+        }
+    }
+}
+
+/// A pair the circuit doesn't add correctly, with the lowest bit of the sum it got wrong.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AdditionFailure {
+    pub x: u64,
+    pub y: u64,
+    pub expected: u64,
+    pub found: u64,
+    pub bit: u32,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CircuitBoard {
     wires: HashMap<String, bool>,
-    gates: VecDeque<Gate>,
+    gates: Vec<Gate>,
 }
 
 impl CircuitBoard {
-    pub fn process(&mut self) {
-        let mut processing = self.gates.clone();
+    /// Evaluates every gate once, in an order where each gate's inputs are already known.
+    /// Gate outputs from an earlier run are overwritten, so the board can be processed again
+    /// after changing its inputs.
+    ///
+    /// # Errors
+    ///
+    /// If the gates can't be ordered, naming the wires in a cycle or the wires nothing
+    /// drives.
+    pub fn process(&mut self) -> Result<(), CircuitError> {
+        for index in self.evaluation_order()? {
+            let wire = self
+                .process_gate(&self.gates[index])
+                .expect("Gate inputs are evaluated first!");
+
+            self.wires.insert(wire.id, wire.state);
+        }
 
-        while let Some(gate) = processing.pop_front() {
-            if let Some(wire) = self.process_gate(&gate) {
-                self.wires.insert(wire.id, wire.state);
-            } else {
-                processing.push_back(gate);
+        Ok(())
+    }
+
+    /// Sets an input wire, to be picked up by the next [`CircuitBoard::process`].
+    pub fn set_input_wire(&mut self, id: &str, state: bool) {
+        self.wires.insert(id.to_string(), state);
+    }
+
+    /// Sets the `x` and `y` input wires to the bits of `x` and `y`, and reads the sum from the
+    /// `z` wires. Bits above the inputs the board has are ignored.
+    ///
+    /// # Errors
+    ///
+    /// If the board can't be evaluated.
+    pub fn add(&mut self, x: u64, y: u64) -> Result<u64, CircuitError> {
+        let input_ids: Vec<String> = self
+            .wires
+            .keys()
+            .filter(|id| id.starts_with('x') || id.starts_with('y'))
+            .cloned()
+            .collect();
+
+        for id in input_ids {
+            let Some(index) = Self::wire_index(&id) else {
+                continue;
+            };
+
+            let value = if id.starts_with('x') { x } else { y };
+
+            self.set_input_wire(&id, value.checked_shr(index).unwrap_or(0) & 1 == 1);
+        }
+
+        self.process()?;
+
+        Ok(self.number_from_wires('z'))
+    }
+
+    /// Returns how many bits wide the `x` input is.
+    pub fn input_bits(&self) -> u32 {
+        self.count_wires('x')
+    }
+
+    /// Returns how many bits wide the `z` output is.
+    pub fn output_bits(&self) -> u32 {
+        let gate_outputs = self
+            .gates
+            .iter()
+            .filter(|gate| gate.output_wire_id.starts_with('z'))
+            .count();
+
+        u32::try_from(gate_outputs).expect("Too many output wires!")
+    }
+
+    /// Adds every pair and compares against the real sum, returning the failure at the lowest
+    /// bit, or `None` if every pair adds up.
+    ///
+    /// # Errors
+    ///
+    /// If the board can't be evaluated.
+    pub fn first_incorrect_sum_bit(
+        &mut self,
+        pairs: &[(u64, u64)],
+    ) -> Result<Option<AdditionFailure>, CircuitError> {
+        let output_mask = u64::BITS
+            .checked_sub(self.output_bits())
+            .map_or(u64::MAX, |unused_bits| {
+                u64::MAX.checked_shr(unused_bits).unwrap_or(0)
+            });
+
+        let mut first_failure: Option<AdditionFailure> = None;
+
+        for (x, y) in pairs {
+            let expected = x.wrapping_add(*y) & output_mask;
+            let found = self.add(*x, *y)?;
+
+            if expected == found {
+                continue;
+            }
+
+            let bit = (expected ^ found).trailing_zeros();
+
+            if first_failure.is_none_or(|failure| bit < failure.bit) {
+                first_failure = Some(AdditionFailure {
+                    x: *x,
+                    y: *y,
+                    expected,
+                    found,
+                    bit,
+                });
             }
         }
+
+        Ok(first_failure)
+    }
+
+    /// Pairs worth adding to test a `bits` wide adder: zero, all ones, every single bit, every
+    /// carry chain, and then `random_pairs` pseudo-random pairs from `seed`. The pairs are
+    /// `u64`s, so an adder wider than 64 bits only gets its lowest 64 bits tested.
+    pub fn adder_test_pairs(bits: u32, random_pairs: usize, seed: u64) -> Vec<(u64, u64)> {
+        let bits = bits.min(u64::BITS);
+        let max = u64::MAX.checked_shr(u64::BITS - bits).unwrap_or(0);
+
+        let mut pairs = vec![(0, 0), (max, 0), (0, max), (max, max), (max, 1)];
+
+        for bit in 0..bits {
+            let value = 1 << bit;
+
+            pairs.extend([(value, 0), (0, value), (value, value), (value - 1, 1)]);
+        }
+
+        let mut state = seed | 1;
+
+        for _ in 0..random_pairs {
+            let x = Self::next_random(&mut state) & max;
+            let y = Self::next_random(&mut state) & max;
+
+            pairs.push((x, y));
+        }
+
+        pairs
     }
 
     pub fn number_from_wires(&self, starts_with: char) -> u64 {
@@ -125,61 +303,193 @@ impl CircuitBoard {
             )
     }
 
-    pub fn gate_outputs_to_swap(&self) -> Vec<(String, String)> {
-        let mut result = Vec::new();
+    /// Looks for up to `max_swaps` swaps of gate outputs that make the board add its `x` and
+    /// `y` inputs into `z`, one wrong bit at a time. The swaps aren't guaranteed to be the
+    /// fewest that work (see [`swap_search::find_output_swaps_bit_by_bit`]).
+    pub fn output_swaps_to_fix_adder(&self, max_swaps: usize) -> Option<Vec<(String, String)>> {
+        let pairs =
+            Self::adder_test_pairs(self.input_bits(), ADDER_TEST_RANDOM_PAIRS, ADDER_TEST_SEED);
+
+        swap_search::find_output_swaps_bit_by_bit(&self.gates, &pairs, max_swaps)
+    }
+
+    /// Writes the gates as a Graphviz digraph (see [`export::to_dot`]).
+    #[cfg(test)]
+    pub fn to_dot(&self) -> String {
+        export::to_dot(&self.gates)
+    }
+
+    /// Writes the gates as a structural Verilog module (see [`export::to_verilog`]).
+    #[cfg(test)]
+    pub fn to_verilog(&self, module_name: &str) -> String {
+        export::to_verilog(&self.gates, module_name)
+    }
+
+    /// A correct `bits` wide ripple-carry adder to compare other boards against, with every
+    /// input set to 0. Only the `x`, `y` and `z` wires follow the puzzle's naming, the internal
+    /// wires get names of its own: `sNN` for the half sum of bit `NN`, `aNN` and `bNN` for the
+    /// two halves of its carry, and `cNN` for its carry out.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is zero.
+    pub fn ripple_carry_adder(bits: u32) -> Self {
+        assert!(bits > 0, "An adder needs at least one bit!");
+
+        let carry_out = |bit: u32| {
+            if bit + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{bit:02}")
+            }
+        };
+
+        let wires = ['x', 'y']
+            .into_iter()
+            .flat_map(|prefix| (0..bits).map(move |bit| (format!("{prefix}{bit:02}"), false)))
+            .collect();
+
+        let mut gates = vec![
+            Gate::new("x00", "y00", "z00", Operation::Xor),
+            Gate::new("x00", "y00", &carry_out(0), Operation::And),
+        ];
+
+        for bit in 1..bits {
+            let [x, y, z, sum, generate, propagate] =
+                ['x', 'y', 'z', 's', 'a', 'b'].map(|prefix| format!("{prefix}{bit:02}"));
+            let carry_in = format!("c{:02}", bit - 1);
+
+            gates.extend([
+                Gate::new(&x, &y, &sum, Operation::Xor),
+                Gate::new(&sum, &carry_in, &z, Operation::Xor),
+                Gate::new(&x, &y, &generate, Operation::And),
+                Gate::new(&sum, &carry_in, &propagate, Operation::And),
+                Gate::new(&generate, &propagate, &carry_out(bit), Operation::Or),
+            ]);
+        }
+
+        CircuitBoard { wires, gates }
+    }
+
+    /// Builds the truth table of `wire` over the inputs it depends on (see
+    /// [`truth_table::truth_table`]).
+    ///
+    /// # Errors
+    ///
+    /// If the wire is unknown, feeds itself, or depends on too many inputs.
+    #[cfg(test)]
+    pub fn truth_table(&self, wire: &str) -> Result<TruthTable, TruthTableError> {
+        truth_table::truth_table(&self.gates, wire)
+    }
 
-        let output_ids_to_gates = self.output_ids_to_gates();
+    /// Checks that both boards compute the same `z` wires (see [`truth_table::compare`]).
+    ///
+    /// # Errors
+    ///
+    /// If either board has a cycle.
+    pub fn compare(&self, other: &CircuitBoard) -> Result<EquivalenceReport, TruthTableError> {
+        truth_table::compare(&self.gates, &other.gates)
+    }
 
-        let xor_non_xy_gates: Vec<&Gate> = self
+    /// Checks the board against a ripple-carry adder as wide as its `x` input.
+    ///
+    /// Bit `n` of an adder depends on `2 * (n + 1)` inputs, so only `z00` to `z09` fit in
+    /// [`truth_table::MAX_CONE_INPUTS`] and are actually checked. The wider bits end up in
+    /// [`EquivalenceReport::unchecked_wires`], which on the puzzle input is most of them.
+    ///
+    /// # Errors
+    ///
+    /// If the board has a cycle.
+    pub fn compare_with_ripple_carry_adder(&self) -> Result<EquivalenceReport, TruthTableError> {
+        self.compare(&Self::ripple_carry_adder(self.input_bits()))
+    }
+
+    /// Orders the gates topologically (see [`graph::topological_order`]), returning their
+    /// indices.
+    fn evaluation_order(&self) -> Result<Vec<usize>, CircuitError> {
+        let drivers = self.output_ids_to_gate_indices()?;
+
+        let mut undriven_wires: Vec<String> = self
             .gates
             .iter()
-            .filter(|gate| !gate.has_xy_inputs() && (gate.operation == Operation::Xor))
+            .flat_map(|gate| [&gate.input_wire_id_a, &gate.input_wire_id_b])
+            .filter(|id| !drivers.contains_key(id.as_str()) && !self.wires.contains_key(*id))
+            .cloned()
+            .collect::<HashSet<String>>()
+            .into_iter()
             .collect();
 
-        for gate in xor_non_xy_gates {
-            let a_gate = output_ids_to_gates.get(&gate.input_wire_id_a).unwrap();
-            let b_gate = output_ids_to_gates.get(&gate.input_wire_id_b).unwrap();
+        if !undriven_wires.is_empty() {
+            undriven_wires.sort();
+
+            return Err(CircuitError::UndrivenWires(undriven_wires));
+        }
 
-            let correct_output_z_id = Self::relative_z_id_for_id(&a_gate.input_wire_id_a)
-                .or(Self::relative_z_id_for_id(&b_gate.input_wire_id_a));
+        let mut dependents = vec![Vec::new(); self.gates.len()];
 
-            if correct_output_z_id != Some(gate.output_wire_id.clone()) {
-                result.push((
-                    gate.output_wire_id.clone(),
-                    correct_output_z_id.clone().unwrap(),
-                ));
+        for (index, gate) in self.gates.iter().enumerate() {
+            for input_id in [&gate.input_wire_id_a, &gate.input_wire_id_b] {
+                if let Some(driver) = drivers.get(input_id.as_str()) {
+                    dependents[*driver].push(index);
+                }
             }
         }
 
-        let or_non_xy_gates: Vec<&Gate> = self
-            .gates
+        graph::topological_order(&dependents).map_err(|GraphError::Cycle(waiting)| {
+            CircuitError::Cycle(self.find_cycle(&drivers, &waiting))
+        })
+    }
+
+    /// Every gate still waiting on an input after Kahn's algorithm reads from another waiting
+    /// gate, so following those inputs backwards must eventually loop. `waiting` has to be
+    /// sorted.
+    fn find_cycle(&self, drivers: &HashMap<&str, usize>, waiting: &[usize]) -> Vec<String> {
+        let waiting_driver = |index: usize| {
+            let gate = &self.gates[index];
+
+            [&gate.input_wire_id_a, &gate.input_wire_id_b]
+                .into_iter()
+                .filter_map(|id| drivers.get(id.as_str()))
+                .find(|driver| waiting.binary_search(driver).is_ok())
+                .copied()
+                .expect("A waiting gate reads from another waiting gate!")
+        };
+
+        let mut visited_at = HashMap::new();
+        let mut path = Vec::new();
+        let mut current = *waiting.first().expect("Some gate is still waiting!");
+
+        while !visited_at.contains_key(&current) {
+            visited_at.insert(current, path.len());
+            path.push(current);
+
+            current = waiting_driver(current);
+        }
+
+        let mut cycle: Vec<String> = path[visited_at[&current]..]
             .iter()
-            .filter(|gate| !gate.has_xy_inputs() && (gate.operation == Operation::Or))
+            .rev()
+            .map(|index| self.gates[*index].output_wire_id.clone())
             .collect();
 
-        for gate in or_non_xy_gates {
-            let relative_xy_gate = output_ids_to_gates
-                .get(&gate.input_wire_id_a)
-                .filter(|gate| gate.has_xy_inputs())
-                .or(output_ids_to_gates.get(&gate.input_wire_id_b))
-                .unwrap();
-
-            let correct_xy_gate = output_ids_to_gates
-                .values()
-                .filter(|g| g.has_input_id(&relative_xy_gate.input_wire_id_a))
-                .filter(|g| g.operation == Operation::And)
-                .last()
-                .unwrap();
-
-            if relative_xy_gate != correct_xy_gate {
-                result.push((
-                    relative_xy_gate.output_wire_id.clone(),
-                    correct_xy_gate.output_wire_id.clone(),
-                ));
+        cycle.push(cycle[0].clone());
+
+        cycle
+    }
+
+    fn output_ids_to_gate_indices(&self) -> Result<HashMap<&str, usize>, CircuitError> {
+        let mut drivers = HashMap::new();
+
+        for (index, gate) in self.gates.iter().enumerate() {
+            if drivers
+                .insert(gate.output_wire_id.as_str(), index)
+                .is_some()
+            {
+                return Err(CircuitError::MultipleDrivers(gate.output_wire_id.clone()));
             }
         }
 
-        result
+        Ok(drivers)
     }
 
     fn process_gate(&self, gate: &Gate) -> Option<Wire> {
@@ -194,13 +504,6 @@ impl CircuitBoard {
         }
     }
 
-    fn output_ids_to_gates(&self) -> HashMap<String, Gate> {
-        self.gates
-            .iter()
-            .map(|gate| (gate.output_wire_id.clone(), gate.clone()))
-            .collect()
-    }
-
     fn wires_sorted(&self, starts_with: char) -> Vec<Wire> {
         let mut wires: Vec<Wire> = self
             .wires
@@ -214,12 +517,28 @@ impl CircuitBoard {
         wires
     }
 
-    fn relative_z_id_for_id(id: &str) -> Option<String> {
-        if id.starts_with('x') || id.starts_with('y') {
-            Some(id.replace(['x', 'y'], "z"))
-        } else {
-            None
-        }
+    fn count_wires(&self, starts_with: char) -> u32 {
+        let count = self
+            .wires
+            .keys()
+            .filter(|id| id.starts_with(starts_with))
+            .count();
+
+        u32::try_from(count).expect("Too many wires!")
+    }
+
+    /// Returns the bit a wire like `x05` stands for.
+    fn wire_index(id: &str) -> Option<u32> {
+        id.get(1..).and_then(|index| index.parse().ok())
+    }
+
+    /// Xorshift, which is plenty for picking test pairs.
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+
+        *state
     }
 
     fn parse_wires_input(input: &[String]) -> HashMap<String, bool> {
@@ -230,7 +549,7 @@ impl CircuitBoard {
             .collect()
     }
 
-    fn parse_gates_input(input: &[String]) -> VecDeque<Gate> {
+    fn parse_gates_input(input: &[String]) -> Vec<Gate> {
         input.iter().map(Gate::from).collect()
     }
 }
@@ -320,7 +639,7 @@ mod tests {
             (String::from("y02"), false),
         ]);
 
-        let expected_gates = VecDeque::from([
+        let expected_gates = Vec::from([
             Gate::new("x00", "y00", "z00", Operation::And),
             Gate::new("x01", "y01", "z01", Operation::Xor),
             Gate::new("x02", "y02", "z02", Operation::Or),
@@ -393,19 +712,6 @@ mod tests {
         assert_eq!(circuit_board.number_from_wires('z'), 87);
     }
 
-    #[test]
-    fn test_circuit_board_relative_z_id_for_id() {
-        assert_eq!(
-            CircuitBoard::relative_z_id_for_id("x12"),
-            Some(String::from("z12"))
-        );
-        assert_eq!(
-            CircuitBoard::relative_z_id_for_id("y02"),
-            Some(String::from("z02"))
-        );
-        assert_eq!(CircuitBoard::relative_z_id_for_id("tst"), None);
-    }
-
     #[test]
     fn test_circuit_board_process_simple() {
         let mut circuit_board = CircuitBoard::from([
@@ -421,8 +727,318 @@ mod tests {
             "x02 OR y02 -> z02",
         ]);
 
-        circuit_board.process();
+        circuit_board.process().unwrap();
 
         assert_eq!(circuit_board.number_from_wires('z'), 4);
     }
+
+    #[test]
+    fn test_circuit_board_process_out_of_order() {
+        let mut circuit_board = CircuitBoard::from([
+            "x00: 1",
+            "y00: 1",
+            "",
+            "abc XOR y00 -> z01",
+            "def OR y00 -> abc",
+            "x00 AND y00 -> def",
+            "x00 XOR y00 -> z00",
+        ]);
+
+        circuit_board.process().unwrap();
+
+        assert_eq!(circuit_board.number_from_wires('z'), 0);
+
+        circuit_board.set_input_wire("y00", false);
+        circuit_board.process().unwrap();
+
+        assert_eq!(circuit_board.number_from_wires('z'), 1);
+    }
+
+    #[test]
+    fn test_circuit_board_process_cycle() {
+        let mut circuit_board = CircuitBoard::from([
+            "x00: 1",
+            "",
+            "x00 AND ccc -> z00",
+            "x00 OR z00 -> aaa",
+            "aaa XOR x00 -> bbb",
+            "bbb AND aaa -> ccc",
+        ]);
+
+        let expected = Err(CircuitError::Cycle(vec![
+            String::from("aaa"),
+            String::from("bbb"),
+            String::from("ccc"),
+            String::from("z00"),
+            String::from("aaa"),
+        ]));
+
+        assert_eq!(circuit_board.process(), expected);
+    }
+
+    #[test]
+    fn test_circuit_board_process_undriven_wires() {
+        let mut circuit_board = CircuitBoard::from([
+            "x00: 1",
+            "",
+            "x00 AND y00 -> z00",
+            "x01 OR z00 -> z01",
+            "y00 OR x00 -> z02",
+        ]);
+
+        let expected = Err(CircuitError::UndrivenWires(vec![
+            String::from("x01"),
+            String::from("y00"),
+        ]));
+
+        assert_eq!(circuit_board.process(), expected);
+    }
+
+    #[test]
+    fn test_circuit_board_process_multiple_drivers() {
+        let mut circuit_board = CircuitBoard::from([
+            "x00: 1",
+            "y00: 1",
+            "",
+            "x00 AND y00 -> z00",
+            "x00 OR y00 -> z00",
+        ]);
+
+        assert_eq!(
+            circuit_board.process(),
+            Err(CircuitError::MultipleDrivers(String::from("z00")))
+        );
+    }
+
+    #[test]
+    fn test_circuit_error_to_string() {
+        let error = CircuitError::Cycle(vec![
+            String::from("aaa"),
+            String::from("bbb"),
+            String::from("aaa"),
+        ]);
+
+        assert_eq!(error.to_string(), "wires form a cycle: aaa -> bbb -> aaa");
+    }
+
+    fn ripple_carry_adder(bits: u32) -> Vec<String> {
+        let mut input = Vec::new();
+
+        for prefix in ['x', 'y'] {
+            input.extend((0..bits).map(|bit| format!("{prefix}{bit:02}: 0")));
+        }
+
+        input.push(String::new());
+        input.push(String::from("x00 XOR y00 -> z00"));
+        input.push(String::from("x00 AND y00 -> c00"));
+
+        for bit in 1..bits {
+            let carry_out = if bit + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{bit:02}")
+            };
+
+            input.extend([
+                format!("x{bit:02} XOR y{bit:02} -> s{bit:02}"),
+                format!("s{bit:02} XOR c{:02} -> z{bit:02}", bit - 1),
+                format!("x{bit:02} AND y{bit:02} -> a{bit:02}"),
+                format!("s{bit:02} AND c{:02} -> b{bit:02}", bit - 1),
+                format!("a{bit:02} OR b{bit:02} -> {carry_out}"),
+            ]);
+        }
+
+        input
+    }
+
+    fn swap_outputs(input: &mut [String], a: &str, b: &str) {
+        for line in input.iter_mut() {
+            if let Some(gate) = line.strip_suffix(a) {
+                *line = format!("{gate}{b}");
+            } else if let Some(gate) = line.strip_suffix(b) {
+                *line = format!("{gate}{a}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_circuit_board_exports() {
+        let circuit_board = CircuitBoard::from(ripple_carry_adder(1).as_slice());
+
+        let expected_verilog = "module half_adder (
+    input wire x00,
+    input wire y00,
+    output wire z00
+);
+
+    wire c00;
+
+    xor g_z00 (z00, x00, y00);
+    and g_c00 (c00, x00, y00);
+endmodule
+";
+
+        assert!(circuit_board
+            .to_dot()
+            .contains("    x00 -> c00;\n    y00 -> c00;\n}"));
+        assert_eq!(circuit_board.to_verilog("half_adder"), expected_verilog);
+    }
+
+    #[test]
+    fn test_circuit_board_add() {
+        let mut circuit_board = CircuitBoard::from(ripple_carry_adder(4).as_slice());
+
+        assert_eq!(circuit_board.input_bits(), 4);
+        assert_eq!(circuit_board.output_bits(), 5);
+        assert_eq!(circuit_board.add(0, 0), Ok(0));
+        assert_eq!(circuit_board.add(5, 6), Ok(11));
+        assert_eq!(circuit_board.add(15, 15), Ok(30));
+        assert_eq!(circuit_board.add(16, 1), Ok(1));
+    }
+
+    #[test]
+    fn test_circuit_board_adder_test_pairs() {
+        let pairs = CircuitBoard::adder_test_pairs(3, 50, 24);
+
+        assert_eq!(pairs.len(), 5 + 3 * 4 + 50);
+        assert_eq!(pairs[..5], [(0, 0), (7, 0), (0, 7), (7, 7), (7, 1)]);
+        assert!(pairs.contains(&(3, 1)));
+        assert!(pairs.iter().all(|(x, y)| *x < 8 && *y < 8));
+        assert_eq!(pairs, CircuitBoard::adder_test_pairs(3, 50, 24));
+        assert_eq!(
+            CircuitBoard::adder_test_pairs(100, 10, 24),
+            CircuitBoard::adder_test_pairs(64, 10, 24)
+        );
+        assert_eq!(CircuitBoard::adder_test_pairs(64, 0, 24)[1], (u64::MAX, 0));
+    }
+
+    #[test]
+    fn test_circuit_board_first_incorrect_sum_bit() {
+        let pairs = CircuitBoard::adder_test_pairs(6, 100, 7);
+
+        let mut correct = CircuitBoard::from(ripple_carry_adder(6).as_slice());
+
+        let mut broken_input = ripple_carry_adder(6);
+        swap_outputs(&mut broken_input, "z03", "c03");
+
+        let mut broken = CircuitBoard::from(broken_input.as_slice());
+
+        let failure = broken.first_incorrect_sum_bit(&pairs).unwrap().unwrap();
+
+        assert_eq!(correct.first_incorrect_sum_bit(&pairs), Ok(None));
+        assert_eq!(failure.bit, 3);
+        assert_eq!(failure.expected, failure.x + failure.y);
+        assert_eq!(broken.add(failure.x, failure.y), Ok(failure.found));
+    }
+
+    #[test]
+    fn test_circuit_board_output_swaps_to_fix_adder() {
+        let mut input = ripple_carry_adder(8);
+        swap_outputs(&mut input, "z03", "c03");
+        swap_outputs(&mut input, "s05", "a05");
+
+        let circuit_board = CircuitBoard::from(input.as_slice());
+
+        let expected = vec![
+            (String::from("c03"), String::from("z03")),
+            (String::from("a05"), String::from("s05")),
+        ];
+
+        assert_eq!(circuit_board.output_swaps_to_fix_adder(4), Some(expected));
+        assert_eq!(circuit_board.output_swaps_to_fix_adder(1), None);
+    }
+
+    #[test]
+    fn test_circuit_board_output_swaps_to_fix_adder_shuffled() {
+        let mut input = ripple_carry_adder(6);
+        swap_outputs(&mut input, "z02", "b02");
+
+        // Rename the internal wires and reverse the gates, so nothing looks like a textbook
+        // ripple-carry adder any more.
+        let gates_start = input.iter().position(String::is_empty).unwrap() + 1;
+
+        input[gates_start..].reverse();
+
+        for line in &mut input[gates_start..] {
+            for (from, to) in [("s0", "q"), ("a0", "w"), ("b0", "kk"), ("c0", "m")] {
+                *line = line.replace(from, to);
+            }
+        }
+
+        let circuit_board = CircuitBoard::from(input.as_slice());
+
+        let expected = vec![(String::from("kk2"), String::from("z02"))];
+
+        assert_eq!(circuit_board.output_swaps_to_fix_adder(4), Some(expected));
+    }
+
+    #[test]
+    fn test_circuit_board_output_swaps_to_fix_correct_adder() {
+        let circuit_board = CircuitBoard::from(ripple_carry_adder(5).as_slice());
+
+        assert_eq!(circuit_board.output_swaps_to_fix_adder(4), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_circuit_board_ripple_carry_adder() {
+        let mut one_bit = CircuitBoard::ripple_carry_adder(1);
+        let mut eight_bits = CircuitBoard::ripple_carry_adder(8);
+
+        assert_eq!(one_bit.add(1, 1), Ok(2));
+        assert_eq!(eight_bits.add(200, 100), Ok(300));
+        assert_eq!(
+            CircuitBoard::ripple_carry_adder(6),
+            CircuitBoard::from(ripple_carry_adder(6).as_slice())
+        );
+    }
+
+    #[test]
+    fn test_circuit_board_truth_table() {
+        let circuit_board = CircuitBoard::ripple_carry_adder(2);
+
+        let result = circuit_board.truth_table("z01").unwrap();
+
+        assert_eq!(result.inputs, ["x00", "x01", "y00", "y01"]);
+        let expected: Vec<bool> = (0..16_u32)
+            .map(|row| {
+                let x = (row >> 3) + ((row >> 2) & 1) * 2;
+                let y = ((row >> 1) & 1) + (row & 1) * 2;
+
+                (x + y) & 2 == 2
+            })
+            .collect();
+
+        assert_eq!(result.rows, expected);
+    }
+
+    #[test]
+    fn test_circuit_board_compare_with_ripple_carry_adder() {
+        let mut input = ripple_carry_adder(12);
+        swap_outputs(&mut input, "s03", "a03");
+
+        let broken = CircuitBoard::from(input.as_slice());
+
+        let expected = EquivalenceReport {
+            differing_wires: vec![
+                String::from("z03"),
+                String::from("z04"),
+                String::from("z05"),
+                String::from("z06"),
+                String::from("z07"),
+                String::from("z08"),
+                String::from("z09"),
+            ],
+            unchecked_wires: vec![
+                String::from("z10"),
+                String::from("z11"),
+                String::from("z12"),
+            ],
+        };
+
+        assert_eq!(broken.compare_with_ripple_carry_adder(), Ok(expected));
+        assert!(CircuitBoard::ripple_carry_adder(6)
+            .compare_with_ripple_carry_adder()
+            .unwrap()
+            .is_equivalent());
+    }
 }
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::circuit_board::{Gate, Operation};
+
+const INDENT: &str = "    ";
+
+/// DOT keywords, which can't be plain IDs in any case.
+const DOT_KEYWORDS: [&str; 6] = ["digraph", "edge", "graph", "node", "strict", "subgraph"];
+
+/// Verilog-2005 keywords, which can't be plain identifiers.
+const VERILOG_KEYWORDS: &str =
+    "always and assign automatic begin buf bufif0 bufif1 case casex casez cell cmos config \
+     deassign default defparam design disable edge else end endcase endconfig endfunction \
+     endgenerate endmodule endprimitive endspecify endtable endtask event for force forever \
+     fork function generate genvar highz0 highz1 if ifnone incdir include initial inout \
+     input instance integer join large liblist library localparam macromodule medium module \
+     nand negedge nmos nor noshowcancelled not notif0 notif1 or output parameter pmos \
+     posedge primitive pull0 pull1 pulldown pullup pulsestyle_ondetect pulsestyle_onevent \
+     rcmos real realtime reg release repeat rnmos rpmos rtran rtranif0 rtranif1 scalared \
+     showcancelled signed small specify specparam strong0 strong1 supply0 supply1 table \
+     task time tran tranif0 tranif1 tri tri0 tri1 triand trior trireg unsigned use uwire \
+     vectored wait wand weak0 weak1 while wire wor xnor xor";
+
+fn dot_colour(operation: Operation) -> &'static str {
+    match operation {
+        Operation::And => "lightblue",
+        Operation::Xor => "lightsalmon",
+        Operation::Or => "palegreen",
+    }
+}
+
+fn verilog_primitive(operation: Operation) -> &'static str {
+    match operation {
+        Operation::And => "and",
+        Operation::Xor => "xor",
+        Operation::Or => "or",
+    }
+}
+
+fn is_plain_identifier(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Escapes `text` for use inside a quoted DOT string.
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes `id` as is if DOT accepts it as a plain ID, and quoted otherwise.
+fn dot_id(id: &str) -> String {
+    if is_plain_identifier(id) && !DOT_KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(id)) {
+        id.to_string()
+    } else {
+        format!("\"{}\"", escape_dot(id))
+    }
+}
+
+/// Writes `id` as is if it's a plain Verilog identifier, and as an escaped identifier
+/// otherwise. An escaped identifier runs from the backslash to the next whitespace, so it
+/// ends with a space, and anything but printable ASCII in it is replaced by `_`.
+fn verilog_id(id: &str) -> String {
+    if is_plain_identifier(id) && !VERILOG_KEYWORDS.split_whitespace().any(|k| k == id) {
+        id.to_string()
+    } else {
+        let printable: String = id
+            .chars()
+            .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+            .collect();
+
+        format!("\\{printable} ")
+    }
+}
+
+/// Wires that gates read but no gate drives, sorted.
+fn input_wire_ids(gates: &[Gate]) -> Vec<&str> {
+    let outputs: HashSet<&str> = gates
+        .iter()
+        .map(|gate| gate.output_wire_id.as_str())
+        .collect();
+
+    let mut inputs: Vec<&str> = gates
+        .iter()
+        .flat_map(|gate| [gate.input_wire_id_a.as_str(), gate.input_wire_id_b.as_str()])
+        .filter(|id| !outputs.contains(id))
+        .collect::<HashSet<&str>>()
+        .into_iter()
+        .collect();
+
+    inputs.sort_unstable();
+
+    inputs
+}
+
+/// Writes the gates as a Graphviz digraph. Every gate is a node named after its output wire
+/// and coloured by its operation, and the `x`, `y` and `z` wires are grouped in clusters.
+pub fn to_dot(gates: &[Gate]) -> String {
+    let inputs = input_wire_ids(gates);
+
+    let mut z_gates: Vec<&Gate> = gates
+        .iter()
+        .filter(|gate| gate.output_wire_id.starts_with('z'))
+        .collect();
+
+    z_gates.sort_unstable_by(|a, b| a.output_wire_id.cmp(&b.output_wire_id));
+
+    let mut dot = String::from("digraph circuit {\n");
+
+    writeln!(dot, "{INDENT}rankdir=LR;").unwrap();
+    writeln!(dot, "{INDENT}node [fontname=\"monospace\"];").unwrap();
+
+    for prefix in ['x', 'y'] {
+        let ids = inputs.iter().filter(|id| id.starts_with(prefix));
+
+        write_cluster(
+            &mut dot,
+            prefix,
+            ids.map(|id| format!("{} [shape=box];", dot_id(id))),
+        );
+    }
+
+    write_cluster(&mut dot, 'z', z_gates.iter().map(|gate| gate_node(gate)));
+
+    let other_inputs: Vec<&&str> = inputs
+        .iter()
+        .filter(|id| !id.starts_with('x') && !id.starts_with('y'))
+        .collect();
+
+    if !other_inputs.is_empty() {
+        dot.push('\n');
+    }
+
+    for id in other_inputs {
+        writeln!(dot, "{INDENT}{} [shape=box];", dot_id(id)).unwrap();
+    }
+
+    dot.push('\n');
+
+    for gate in gates
+        .iter()
+        .filter(|gate| !gate.output_wire_id.starts_with('z'))
+    {
+        writeln!(dot, "{INDENT}{}", gate_node(gate)).unwrap();
+    }
+
+    dot.push('\n');
+
+    for gate in gates {
+        for input in [&gate.input_wire_id_a, &gate.input_wire_id_b] {
+            writeln!(
+                dot,
+                "{INDENT}{} -> {};",
+                dot_id(input),
+                dot_id(&gate.output_wire_id)
+            )
+            .unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+fn write_cluster<I>(dot: &mut String, prefix: char, nodes: I)
+where
+    I: Iterator<Item = String>,
+{
+    writeln!(dot, "\n{INDENT}subgraph cluster_{prefix} {{").unwrap();
+    writeln!(dot, "{INDENT}{INDENT}label=\"{prefix}\";").unwrap();
+
+    for node in nodes {
+        writeln!(dot, "{INDENT}{INDENT}{node}").unwrap();
+    }
+
+    writeln!(dot, "{INDENT}}}").unwrap();
+}
+
+fn gate_node(gate: &Gate) -> String {
+    format!(
+        "{} [label=\"{}\\n{}\", style=filled, fillcolor={}];",
+        dot_id(&gate.output_wire_id),
+        gate.operation,
+        escape_dot(&gate.output_wire_id),
+        dot_colour(gate.operation)
+    )
+}
+
+/// Writes the gates as a structural Verilog module built from `and`, `or` and `xor`
+/// primitives. Undriven wires become inputs, `z` wires become outputs, and every other gate
+/// output is an internal wire.
+pub fn to_verilog(gates: &[Gate], module_name: &str) -> String {
+    let inputs = input_wire_ids(gates);
+
+    let mut outputs: Vec<&str> = Vec::new();
+    let mut internal_wires: Vec<&str> = Vec::new();
+
+    for gate in gates {
+        if gate.output_wire_id.starts_with('z') {
+            outputs.push(&gate.output_wire_id);
+        } else {
+            internal_wires.push(&gate.output_wire_id);
+        }
+    }
+
+    outputs.sort_unstable();
+    internal_wires.sort_unstable();
+
+    let ports: Vec<String> = inputs
+        .iter()
+        .map(|id| format!("input wire {}", verilog_id(id)))
+        .chain(
+            outputs
+                .iter()
+                .map(|id| format!("output wire {}", verilog_id(id))),
+        )
+        .collect();
+
+    let mut verilog = format!("module {} (\n", verilog_id(module_name));
+
+    for (index, port) in ports.iter().enumerate() {
+        let separator = if index + 1 == ports.len() { "" } else { "," };
+
+        writeln!(verilog, "{INDENT}{port}{separator}").unwrap();
+    }
+
+    verilog.push_str(");\n");
+
+    if !internal_wires.is_empty() {
+        verilog.push('\n');
+    }
+
+    for id in internal_wires {
+        writeln!(verilog, "{INDENT}wire {};", verilog_id(id)).unwrap();
+    }
+
+    verilog.push('\n');
+
+    for gate in gates {
+        writeln!(
+            verilog,
+            "{INDENT}{} {} ({}, {}, {});",
+            verilog_primitive(gate.operation),
+            verilog_id(&format!("g_{}", gate.output_wire_id)),
+            verilog_id(&gate.output_wire_id),
+            verilog_id(&gate.input_wire_id_a),
+            verilog_id(&gate.input_wire_id_b)
+        )
+        .unwrap();
+    }
+
+    verilog.push_str("endmodule\n");
+
+    verilog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_adder() -> Vec<Gate> {
+        [
+            "x00 XOR y00 -> sum",
+            "sum XOR cin -> z00",
+            "x00 AND y00 -> gen",
+            "sum AND cin -> prp",
+            "gen OR prp -> z01",
+        ]
+        .into_iter()
+        .map(Gate::from)
+        .collect()
+    }
+
+    #[test]
+    fn test_input_wire_ids() {
+        assert_eq!(input_wire_ids(&full_adder()), ["cin", "x00", "y00"]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let expected = r#"digraph circuit {
+    rankdir=LR;
+    node [fontname="monospace"];
+
+    subgraph cluster_x {
+        label="x";
+        x00 [shape=box];
+    }
+
+    subgraph cluster_y {
+        label="y";
+        y00 [shape=box];
+    }
+
+    subgraph cluster_z {
+        label="z";
+        z00 [label="XOR\nz00", style=filled, fillcolor=lightsalmon];
+        z01 [label="OR\nz01", style=filled, fillcolor=palegreen];
+    }
+
+    cin [shape=box];
+
+    sum [label="XOR\nsum", style=filled, fillcolor=lightsalmon];
+    gen [label="AND\ngen", style=filled, fillcolor=lightblue];
+    prp [label="AND\nprp", style=filled, fillcolor=lightblue];
+
+    x00 -> sum;
+    y00 -> sum;
+    sum -> z00;
+    cin -> z00;
+    x00 -> gen;
+    y00 -> gen;
+    sum -> prp;
+    cin -> prp;
+    gen -> z01;
+    prp -> z01;
+}
+"#;
+
+        assert_eq!(to_dot(&full_adder()), expected);
+    }
+
+    #[test]
+    fn test_to_verilog() {
+        let expected = "module full_adder (
+    input wire cin,
+    input wire x00,
+    input wire y00,
+    output wire z00,
+    output wire z01
+);
+
+    wire gen;
+    wire prp;
+    wire sum;
+
+    xor g_sum (sum, x00, y00);
+    xor g_z00 (z00, sum, cin);
+    and g_gen (gen, x00, y00);
+    and g_prp (prp, sum, cin);
+    or g_z01 (z01, gen, prp);
+endmodule
+";
+
+        assert_eq!(to_verilog(&full_adder(), "full_adder"), expected);
+    }
+
+    #[test]
+    fn test_awkward_wire_ids() {
+        let gates: Vec<Gate> = ["x00 AND a\"b -> wire", "wire OR 1ab -> node"]
+            .into_iter()
+            .map(Gate::from)
+            .collect();
+
+        let dot = to_dot(&gates);
+
+        assert!(dot.contains(r#"    "a\"b" -> wire;"#));
+        assert!(dot.contains(r#"    "1ab" [shape=box];"#));
+        assert!(dot.contains(r#"    "node" [label="OR\nnode", "#));
+        assert!(dot.contains(r#"    wire -> "node";"#));
+
+        let verilog = to_verilog(&gates, "module");
+
+        assert!(verilog.starts_with("module \\module  (\n"));
+        assert!(verilog.contains("    input wire \\a\"b ,\n"));
+        assert!(verilog.contains("    wire \\wire ;\n"));
+        assert!(verilog.contains("    and g_wire (\\wire , x00, \\a\"b );\n"));
+        assert!(verilog.contains("    or g_node (node, \\wire , \\1ab );\n"));
+    }
+}
@@ -1,20 +1,29 @@
 mod circuit_board;
+#[cfg(test)]
+mod export;
+mod swap_search;
+mod truth_table;
 
 use crate::util::file_reader::to_string_vector;
 
-use circuit_board::CircuitBoard;
+use circuit_board::{CircuitBoard, ADDER_TEST_RANDOM_PAIRS, ADDER_TEST_SEED};
+
+const MAX_SWAPS: usize = 4;
 
 pub fn run() {
     let input = to_string_vector("inputs/day_24.txt").expect("Something went wrong with Day 24!");
 
     println!("Day 24 Part 1: {:?}", part_1(&input));
     println!("Day 24 Part 2: {:?}", part_2(&input));
+    println!("Day 24 Diagnosis: {}", diagnosis(&input));
 }
 
 fn part_1(input: &[String]) -> u64 {
     let mut circuit_board = CircuitBoard::from(input);
 
-    circuit_board.process();
+    circuit_board
+        .process()
+        .expect("The circuit board can't be evaluated!");
 
     circuit_board.number_from_wires('z')
 }
@@ -24,7 +33,10 @@ fn part_2(input: &[String]) -> String {
 
     let mut outputs_to_swap = Vec::new();
 
-    for (id_a, id_b) in circuit_board.gate_outputs_to_swap() {
+    for (id_a, id_b) in circuit_board
+        .output_swaps_to_fix_adder(MAX_SWAPS)
+        .expect("No swaps make the circuit board add!")
+    {
         outputs_to_swap.push(id_a.clone());
         outputs_to_swap.push(id_b.clone());
     }
@@ -34,6 +46,49 @@ fn part_2(input: &[String]) -> String {
     outputs_to_swap.join(",")
 }
 
+/// Describes where the board first stops adding correctly, before any outputs are swapped, and
+/// which of its outputs differ from a ripple-carry adder's.
+fn diagnosis(input: &[String]) -> String {
+    let mut circuit_board = CircuitBoard::from(input);
+
+    let pairs = CircuitBoard::adder_test_pairs(
+        circuit_board.input_bits(),
+        ADDER_TEST_RANDOM_PAIRS,
+        ADDER_TEST_SEED,
+    );
+
+    let addition = match circuit_board
+        .first_incorrect_sum_bit(&pairs)
+        .expect("The circuit board can't be evaluated!")
+    {
+        Some(failure) => format!(
+            "first wrong at bit {}, {} + {} gave {}",
+            failure.bit, failure.x, failure.y, failure.found
+        ),
+        None => String::from("adds every test pair correctly"),
+    };
+
+    let report = circuit_board
+        .compare_with_ripple_carry_adder()
+        .expect("The circuit board can't be compared!");
+
+    let comparison = if report.is_equivalent() {
+        String::from("matches a ripple-carry adder")
+    } else if report.differing_wires.is_empty() {
+        format!(
+            "matches a ripple-carry adder except for {} wires too wide to check",
+            report.unchecked_wires.len()
+        )
+    } else {
+        format!(
+            "differs from a ripple-carry adder at {}",
+            report.differing_wires.join(", ")
+        )
+    };
+
+    format!("{addition}, {comparison}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +99,37 @@ mod tests {
 
         assert_eq!(part_1(&input), 2_024);
     }
+
+    #[test]
+    fn test_diagnosis() {
+        let mut input: Vec<String> = [
+            "x00: 0",
+            "x01: 0",
+            "y00: 0",
+            "y01: 0",
+            "",
+            "x00 XOR y00 -> z00",
+            "x00 AND y00 -> c00",
+            "x01 XOR y01 -> s01",
+            "s01 XOR c00 -> z01",
+            "x01 AND y01 -> a01",
+            "s01 AND c00 -> b01",
+            "a01 OR b01 -> z02",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        assert_eq!(
+            diagnosis(&input),
+            "adds every test pair correctly, matches a ripple-carry adder"
+        );
+
+        input[5] = String::from("x00 XOR y00 -> c00");
+        input[6] = String::from("x00 AND y00 -> z00");
+
+        assert_eq!(
+            diagnosis(&input),
+            "first wrong at bit 0, 3 + 0 gave 4, differs from a ripple-carry adder at z00, z01, z02"
+        );
+    }
 }
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+
+use crate::util::graph;
+
+use super::circuit_board::{CircuitBoard, Gate};
+
+const QUICK_RANDOM_PAIRS: usize = 16;
+
+/// The gates of a board with every wire replaced by an index, so candidate swaps can be
+/// simulated without touching any strings.
+#[derive(Debug, Clone)]
+struct Netlist<'a> {
+    gates: &'a [Gate],
+    wire_ids: Vec<&'a str>,
+    inputs: Vec<(usize, usize)>,
+    outputs: Vec<usize>,
+    x_wires: Vec<(u32, usize)>,
+    y_wires: Vec<(u32, usize)>,
+    z_wires: Vec<(u32, usize)>,
+}
+
+impl<'a> Netlist<'a> {
+    fn new(gates: &'a [Gate]) -> Self {
+        let mut wire_indices: HashMap<&str, usize> = HashMap::new();
+        let mut wire_ids = Vec::new();
+
+        let mut index_of = |id: &'a str| {
+            *wire_indices.entry(id).or_insert_with(|| {
+                wire_ids.push(id);
+
+                wire_ids.len() - 1
+            })
+        };
+
+        let inputs = gates
+            .iter()
+            .map(|gate| {
+                (
+                    index_of(&gate.input_wire_id_a),
+                    index_of(&gate.input_wire_id_b),
+                )
+            })
+            .collect();
+        let outputs = gates
+            .iter()
+            .map(|gate| index_of(&gate.output_wire_id))
+            .collect();
+
+        let wires_starting_with = |prefix: char| {
+            let mut wires: Vec<(u32, usize)> = wire_ids
+                .iter()
+                .enumerate()
+                .filter(|(_, id)| id.starts_with(prefix))
+                .filter_map(|(index, id)| Some((id.get(1..)?.parse().ok()?, index)))
+                .collect();
+
+            wires.sort_unstable();
+
+            wires
+        };
+
+        Netlist {
+            gates,
+            x_wires: wires_starting_with('x'),
+            y_wires: wires_starting_with('y'),
+            z_wires: wires_starting_with('z'),
+            wire_ids,
+            inputs,
+            outputs,
+        }
+    }
+
+    /// Orders the gates so every gate comes after the gates it reads from, or returns `None`
+    /// if they form a cycle.
+    fn evaluation_order(&self) -> Option<Vec<usize>> {
+        let mut driver = vec![None; self.wire_ids.len()];
+
+        for (gate, output) in self.outputs.iter().enumerate() {
+            driver[*output] = Some(gate);
+        }
+
+        let mut dependents = vec![Vec::new(); self.gates.len()];
+
+        for (gate, (input_a, input_b)) in self.inputs.iter().enumerate() {
+            for input in [input_a, input_b] {
+                if let Some(source) = driver[*input] {
+                    dependents[source].push(gate);
+                }
+            }
+        }
+
+        graph::topological_order(&dependents).ok()
+    }
+
+    /// Sums are `u64`s, so `z` wires from bit 64 up are left out of them.
+    fn add(&self, order: &[usize], states: &mut [bool], x: u64, y: u64) -> u64 {
+        for (bit, wire) in &self.x_wires {
+            states[*wire] = x.checked_shr(*bit).unwrap_or(0) & 1 == 1;
+        }
+
+        for (bit, wire) in &self.y_wires {
+            states[*wire] = y.checked_shr(*bit).unwrap_or(0) & 1 == 1;
+        }
+
+        for gate in order {
+            let (input_a, input_b) = self.inputs[*gate];
+
+            states[self.outputs[*gate]] = self.gates[*gate].apply(states[input_a], states[input_b]);
+        }
+
+        self.z_wires
+            .iter()
+            .filter(|(_, wire)| states[*wire])
+            .fold(0, |sum, (bit, _)| {
+                sum | 1_u64.checked_shl(*bit).unwrap_or(0)
+            })
+    }
+
+    /// Returns the lowest bit of the sum that's wrong for any of the pairs, treating a cycle
+    /// as wrong from bit 0. Like [`Netlist::add`], only the lowest 64 bits are checked.
+    fn lowest_failing_bit(&self, pairs: &[(u64, u64)]) -> Option<u32> {
+        let Some(order) = self.evaluation_order() else {
+            return Some(0);
+        };
+
+        let output_mask = self.z_wires.iter().fold(0_u64, |mask, (bit, _)| {
+            mask | 1_u64.checked_shl(*bit).unwrap_or(0)
+        });
+
+        let mut states = vec![false; self.wire_ids.len()];
+
+        pairs
+            .iter()
+            .filter_map(|(x, y)| {
+                let expected = x.wrapping_add(*y) & output_mask;
+                let found = self.add(&order, &mut states, *x, *y);
+
+                (expected != found).then(|| (expected ^ found).trailing_zeros())
+            })
+            .min()
+    }
+
+    /// Marks every gate whose output can reach one of the `z` wires with a bit in `bits`.
+    fn cone<R>(&self, bits: R) -> Vec<bool>
+    where
+        R: RangeBounds<u32>,
+    {
+        let mut in_cone = vec![false; self.gates.len()];
+
+        let mut wires: Vec<usize> = self
+            .z_wires
+            .iter()
+            .filter(|(bit, _)| bits.contains(bit))
+            .map(|(_, wire)| *wire)
+            .collect();
+
+        while let Some(wire) = wires.pop() {
+            for (gate, output) in self.outputs.iter().enumerate() {
+                if *output == wire && !in_cone[gate] {
+                    in_cone[gate] = true;
+
+                    wires.extend([self.inputs[gate].0, self.inputs[gate].1]);
+                }
+            }
+        }
+
+        in_cone
+    }
+
+    fn swap_outputs(&mut self, gate_a: usize, gate_b: usize) {
+        self.outputs.swap(gate_a, gate_b);
+    }
+}
+
+/// Looks for up to `max_swaps` swaps of gate outputs after which the gates add the `x` and
+/// `y` wires into the `z` wires for every pair in `pairs`, fixing the lowest wrong bit with
+/// each swap.
+///
+/// This is a heuristic, not a search for the smallest set of swaps. Each swap has to fix the
+/// lowest bit that's wrong on its own without breaking a lower one: one of its gates has to
+/// feed that bit, and since the lower bits already add up, neither may feed a lower bit.
+/// Fixes that need two swaps together before any bit improves are never found, and a smaller
+/// set of swaps that doesn't fix one bit at a time can be missed. Candidates are tried
+/// against a few pairs around the bit before all of `pairs`. Nothing depends on how the gates
+/// are named or ordered beyond the `x`, `y` and `z` wires.
+pub fn find_output_swaps_bit_by_bit(
+    gates: &[Gate],
+    pairs: &[(u64, u64)],
+    max_swaps: usize,
+) -> Option<Vec<(String, String)>> {
+    let mut netlist = Netlist::new(gates);
+    let mut swaps = Vec::new();
+
+    (0..=max_swaps)
+        .find(|limit| search(&mut netlist, pairs, *limit, &mut swaps))
+        .map(|_| {
+            swaps
+                .iter()
+                .map(|(gate_a, gate_b)| {
+                    let mut ids = [
+                        gates[*gate_a].output_wire_id.clone(),
+                        gates[*gate_b].output_wire_id.clone(),
+                    ];
+
+                    ids.sort();
+
+                    let [id_a, id_b] = ids;
+
+                    (id_a, id_b)
+                })
+                .collect()
+        })
+}
+
+fn search(
+    netlist: &mut Netlist,
+    pairs: &[(u64, u64)],
+    limit: usize,
+    swaps: &mut Vec<(usize, usize)>,
+) -> bool {
+    let Some(bit) = netlist.lowest_failing_bit(pairs) else {
+        return true;
+    };
+
+    if swaps.len() == limit {
+        return false;
+    }
+
+    let quick_pairs = pairs_around_bit(bit, netlist.x_wires.len());
+    let is_swapped = |gate: usize, swaps: &[(usize, usize)]| {
+        swaps
+            .iter()
+            .any(|(gate_a, gate_b)| *gate_a == gate || *gate_b == gate)
+    };
+
+    let in_cone = netlist.cone(bit..=bit);
+    let settled = netlist.cone(..bit);
+
+    let candidates: Vec<usize> = (0..netlist.gates.len())
+        .filter(|gate| !settled[*gate] && !is_swapped(*gate, swaps))
+        .collect();
+
+    for gate_a in candidates.iter().copied().filter(|gate| in_cone[*gate]) {
+        for gate_b in candidates.iter().copied() {
+            // Pairs of gates that both feed the bit are tried once, from the lower index.
+            if gate_a == gate_b || (in_cone[gate_b] && gate_b < gate_a) {
+                continue;
+            }
+
+            netlist.swap_outputs(gate_a, gate_b);
+
+            let fixes_bit = |netlist: &Netlist, pairs: &[(u64, u64)]| {
+                netlist
+                    .lowest_failing_bit(pairs)
+                    .is_none_or(|failing_bit| failing_bit > bit)
+            };
+
+            if fixes_bit(netlist, &quick_pairs) && fixes_bit(netlist, pairs) {
+                swaps.push((gate_a, gate_b));
+
+                if search(netlist, pairs, limit, swaps) {
+                    return true;
+                }
+
+                swaps.pop();
+            }
+
+            netlist.swap_outputs(gate_a, gate_b);
+        }
+    }
+
+    false
+}
+
+/// Edge cases and random pairs whose highest set bit is close to `bit`.
+fn pairs_around_bit(bit: u32, input_bits: usize) -> Vec<(u64, u64)> {
+    let input_bits = u32::try_from(input_bits).expect("Too many input wires!");
+    let lowest_bit = bit.saturating_sub(1);
+
+    CircuitBoard::adder_test_pairs(
+        (bit + 2).min(input_bits),
+        QUICK_RANDOM_PAIRS,
+        u64::from(bit),
+    )
+    .into_iter()
+    .filter(|(x, y)| (x | y).checked_shr(lowest_bit).unwrap_or(0) != 0)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gates(lines: &[&str]) -> Vec<Gate> {
+        lines.iter().copied().map(Gate::from).collect()
+    }
+
+    #[test]
+    fn test_netlist_add() {
+        let gates = gates(&[
+            "x01 XOR y01 -> s01",
+            "s01 XOR c00 -> z01",
+            "x00 XOR y00 -> z00",
+            "x00 AND y00 -> c00",
+            "x01 AND y01 -> a01",
+            "s01 AND c00 -> b01",
+            "a01 OR b01 -> z02",
+        ]);
+
+        let netlist = Netlist::new(&gates);
+        let order = netlist.evaluation_order().unwrap();
+
+        let mut states = vec![false; netlist.wire_ids.len()];
+
+        let result: Vec<u64> = [(0, 0), (1, 1), (3, 2), (3, 3)]
+            .into_iter()
+            .map(|(x, y)| netlist.add(&order, &mut states, x, y))
+            .collect();
+
+        assert_eq!(result, [0, 2, 5, 6]);
+        assert_eq!(netlist.lowest_failing_bit(&[(3, 3), (1, 2)]), None);
+    }
+
+    #[test]
+    fn test_netlist_cycle_fails_from_bit_0() {
+        let gates = gates(&["x00 XOR aaa -> z00", "z00 AND y00 -> aaa"]);
+
+        let netlist = Netlist::new(&gates);
+
+        assert_eq!(netlist.evaluation_order(), None);
+        assert_eq!(netlist.lowest_failing_bit(&[(0, 0)]), Some(0));
+    }
+
+    #[test]
+    fn test_netlist_skips_bits_from_64() {
+        let gates = gates(&[
+            "x00 XOR y00 -> z00",
+            "x00 AND y00 -> z64",
+            "x00 OR y00 -> z65",
+        ]);
+
+        let netlist = Netlist::new(&gates);
+        let order = netlist.evaluation_order().unwrap();
+
+        let mut states = vec![false; netlist.wire_ids.len()];
+
+        assert_eq!(netlist.add(&order, &mut states, 1, 1), 0);
+        assert_eq!(netlist.add(&order, &mut states, 1, 0), 1);
+        assert_eq!(netlist.lowest_failing_bit(&[(0, 1), (1, 1)]), None);
+    }
+
+    #[test]
+    fn test_netlist_cone() {
+        let gates = gates(&[
+            "x00 XOR y00 -> z00",
+            "x00 AND y00 -> c00",
+            "x01 XOR y01 -> s01",
+            "s01 XOR c00 -> z01",
+        ]);
+
+        let netlist = Netlist::new(&gates);
+
+        assert_eq!(netlist.cone(0..=0), [true, false, false, false]);
+        assert_eq!(netlist.cone(1..=1), [false, true, true, true]);
+        assert_eq!(netlist.cone(..1), [true, false, false, false]);
+        assert_eq!(netlist.cone(2..), [false; 4]);
+    }
+
+    #[test]
+    fn test_find_output_swaps_bit_by_bit() {
+        let gates = gates(&[
+            "x00 XOR y00 -> c00",
+            "x00 AND y00 -> z00",
+            "x01 XOR y01 -> s01",
+            "s01 XOR c00 -> z01",
+            "x01 AND y01 -> a01",
+            "s01 AND c00 -> b01",
+            "a01 OR b01 -> z02",
+        ]);
+
+        let pairs = CircuitBoard::adder_test_pairs(2, 0, 1);
+
+        let expected = vec![(String::from("c00"), String::from("z00"))];
+
+        assert_eq!(
+            find_output_swaps_bit_by_bit(&gates, &pairs, 2),
+            Some(expected)
+        );
+        assert_eq!(find_output_swaps_bit_by_bit(&gates, &pairs, 0), None);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use super::circuit_board::Gate;
+
+/// Cones with more inputs than this aren't evaluated exhaustively.
+pub const MAX_CONE_INPUTS: usize = 20;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TruthTableError {
+    UnknownWire(String),
+    /// Holds a wire that (indirectly) feeds itself.
+    Cycle(String),
+    #[cfg(test)]
+    ConeTooLarge {
+        wire: String,
+        inputs: usize,
+    },
+}
+
+impl fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TruthTableError::UnknownWire(wire) => write!(f, "no gate reads or writes {wire}"),
+            TruthTableError::Cycle(wire) => write!(f, "wire {wire} feeds itself"),
+            #[cfg(test)]
+            TruthTableError::ConeTooLarge { wire, inputs } => write!(
+                f,
+                "wire {wire} depends on {inputs} inputs, more than {MAX_CONE_INPUTS}"
+            ),
+        }
+    }
+}
+
+/// The value of `wire` for every combination of the inputs it depends on. Row `r` sets
+/// `inputs[i]` to bit `inputs.len() - 1 - i` of `r`, so the first input is the most
+/// significant, like a truth table written out by hand.
+#[cfg(test)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TruthTable {
+    pub wire: String,
+    pub inputs: Vec<String>,
+    pub rows: Vec<bool>,
+}
+
+#[cfg(test)]
+impl fmt::Display for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} | {}", self.inputs.join(" "), self.wire)?;
+
+        for (row, value) in self.rows.iter().enumerate() {
+            let cells: Vec<String> = self
+                .inputs
+                .iter()
+                .enumerate()
+                .map(|(index, input)| {
+                    let bit = row_bit(row, index, self.inputs.len());
+
+                    format!("{:>width$}", u8::from(bit), width = input.len())
+                })
+                .collect();
+
+            write!(f, "{} | {}", cells.join(" "), u8::from(*value))?;
+
+            if row + 1 < self.rows.len() {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wires whose behaviour two sets of gates disagree on, and wires with cones too large to
+/// compare exhaustively.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct EquivalenceReport {
+    pub differing_wires: Vec<String>,
+    pub unchecked_wires: Vec<String>,
+}
+
+impl EquivalenceReport {
+    pub fn is_equivalent(&self) -> bool {
+        self.differing_wires.is_empty() && self.unchecked_wires.is_empty()
+    }
+}
+
+/// The gates that `wire` depends on, in evaluation order, and the undriven wires they read.
+#[derive(Debug)]
+struct Cone<'a> {
+    wire: &'a str,
+    inputs: BTreeSet<&'a str>,
+    gates: Vec<&'a Gate>,
+}
+
+impl<'a> Cone<'a> {
+    fn new(gates: &'a [Gate], wire: &'a str) -> Result<Self, TruthTableError> {
+        let drivers: HashMap<&str, &Gate> = gates
+            .iter()
+            .map(|gate| (gate.output_wire_id.as_str(), gate))
+            .collect();
+
+        let is_read = gates
+            .iter()
+            .any(|gate| gate.input_wire_id_a == wire || gate.input_wire_id_b == wire);
+
+        if !drivers.contains_key(wire) && !is_read {
+            return Err(TruthTableError::UnknownWire(wire.to_string()));
+        }
+
+        let mut cone = Cone {
+            wire,
+            inputs: BTreeSet::new(),
+            gates: Vec::new(),
+        };
+
+        cone.visit(&drivers, wire, &mut HashMap::new())?;
+
+        Ok(cone)
+    }
+
+    /// Depth-first search that adds each gate after the gates it reads from. `finished`
+    /// maps a wire to whether its gate has been added, so an unfinished wire met again is a
+    /// cycle.
+    fn visit(
+        &mut self,
+        drivers: &HashMap<&str, &'a Gate>,
+        wire: &'a str,
+        finished: &mut HashMap<&'a str, bool>,
+    ) -> Result<(), TruthTableError> {
+        let Some(gate) = drivers.get(wire).copied() else {
+            self.inputs.insert(wire);
+
+            return Ok(());
+        };
+
+        match finished.get(wire) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(TruthTableError::Cycle(wire.to_string())),
+            None => {}
+        }
+
+        finished.insert(wire, false);
+
+        self.visit(drivers, &gate.input_wire_id_a, finished)?;
+        self.visit(drivers, &gate.input_wire_id_b, finished)?;
+
+        finished.insert(wire, true);
+        self.gates.push(gate);
+
+        Ok(())
+    }
+
+    /// Numbers the wires for evaluating over `inputs`, which has to include every input of the
+    /// cone: the inputs come first, followed by the gate outputs in evaluation order.
+    fn compile(&self, inputs: &[&str]) -> CompiledCone<'a> {
+        let mut indices: HashMap<&str, usize> = inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| (*input, index))
+            .collect();
+
+        let gate_inputs = self
+            .gates
+            .iter()
+            .map(|gate| {
+                let wires = (
+                    indices[gate.input_wire_id_a.as_str()],
+                    indices[gate.input_wire_id_b.as_str()],
+                );
+
+                indices.insert(&gate.output_wire_id, indices.len());
+
+                wires
+            })
+            .collect();
+
+        CompiledCone {
+            input_count: inputs.len(),
+            gates: self.gates.clone(),
+            gate_inputs,
+            output: indices[self.wire],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompiledCone<'a> {
+    input_count: usize,
+    gates: Vec<&'a Gate>,
+    gate_inputs: Vec<(usize, usize)>,
+    output: usize,
+}
+
+impl CompiledCone<'_> {
+    /// Evaluates the wire for row `row` of the table, using `states` as scratch space.
+    fn evaluate(&self, row: usize, states: &mut Vec<bool>) -> bool {
+        states.clear();
+        states.extend((0..self.input_count).map(|index| row_bit(row, index, self.input_count)));
+
+        for (gate, (input_a, input_b)) in self.gates.iter().zip(&self.gate_inputs) {
+            let state = gate.apply(states[*input_a], states[*input_b]);
+
+            states.push(state);
+        }
+
+        states[self.output]
+    }
+}
+
+fn row_bit(row: usize, index: usize, input_count: usize) -> bool {
+    (row >> (input_count - 1 - index)) & 1 == 1
+}
+
+/// Builds the truth table of `wire` over the inputs of its cone.
+///
+/// # Errors
+///
+/// If no gate uses `wire`, its cone has a cycle, or it has more than [`MAX_CONE_INPUTS`]
+/// inputs.
+#[cfg(test)]
+pub fn truth_table(gates: &[Gate], wire: &str) -> Result<TruthTable, TruthTableError> {
+    let cone = Cone::new(gates, wire)?;
+
+    let inputs: Vec<&str> = cone.inputs.iter().copied().collect();
+
+    if inputs.len() > MAX_CONE_INPUTS {
+        return Err(TruthTableError::ConeTooLarge {
+            wire: wire.to_string(),
+            inputs: inputs.len(),
+        });
+    }
+
+    let compiled = cone.compile(&inputs);
+    let mut states = Vec::new();
+
+    Ok(TruthTable {
+        wire: wire.to_string(),
+        inputs: inputs.iter().map(ToString::to_string).collect(),
+        rows: (0..(1 << inputs.len()))
+            .map(|row| compiled.evaluate(row, &mut states))
+            .collect(),
+    })
+}
+
+/// Compares every `z` wire of two sets of gates over the union of their input cones. A `z`
+/// wire only one side has counts as differing.
+///
+/// # Errors
+///
+/// If a cone has a cycle.
+pub fn compare(left: &[Gate], right: &[Gate]) -> Result<EquivalenceReport, TruthTableError> {
+    let z_wires: BTreeSet<&str> = left
+        .iter()
+        .chain(right)
+        .map(|gate| gate.output_wire_id.as_str())
+        .filter(|id| id.starts_with('z'))
+        .collect();
+
+    let mut report = EquivalenceReport::default();
+
+    for wire in z_wires {
+        let (left_cone, right_cone) = match (Cone::new(left, wire), Cone::new(right, wire)) {
+            (Ok(left_cone), Ok(right_cone)) => (left_cone, right_cone),
+            (Err(TruthTableError::UnknownWire(_)), _)
+            | (_, Err(TruthTableError::UnknownWire(_))) => {
+                report.differing_wires.push(wire.to_string());
+
+                continue;
+            }
+            (Err(error), _) | (_, Err(error)) => return Err(error),
+        };
+
+        let inputs: Vec<&str> = left_cone
+            .inputs
+            .union(&right_cone.inputs)
+            .copied()
+            .collect();
+
+        if inputs.len() > MAX_CONE_INPUTS {
+            report.unchecked_wires.push(wire.to_string());
+
+            continue;
+        }
+
+        let (left_compiled, right_compiled) =
+            (left_cone.compile(&inputs), right_cone.compile(&inputs));
+        let (mut left_states, mut right_states) = (Vec::new(), Vec::new());
+
+        if (0..(1 << inputs.len())).any(|row| {
+            left_compiled.evaluate(row, &mut left_states)
+                != right_compiled.evaluate(row, &mut right_states)
+        }) {
+            report.differing_wires.push(wire.to_string());
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gates(lines: &[&str]) -> Vec<Gate> {
+        lines.iter().copied().map(Gate::from).collect()
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let gates = gates(&["x00 XOR y00 -> sum", "sum AND cin -> z00"]);
+
+        let expected = TruthTable {
+            wire: String::from("z00"),
+            inputs: vec![
+                String::from("cin"),
+                String::from("x00"),
+                String::from("y00"),
+            ],
+            rows: vec![false, false, false, false, false, true, true, false],
+        };
+
+        let result = truth_table(&gates, "z00");
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_truth_table_of_input() {
+        let gates = gates(&["x00 XOR y00 -> z00"]);
+
+        let expected = TruthTable {
+            wire: String::from("x00"),
+            inputs: vec![String::from("x00")],
+            rows: vec![false, true],
+        };
+
+        assert_eq!(truth_table(&gates, "x00"), Ok(expected));
+    }
+
+    #[test]
+    fn test_truth_table_to_string() {
+        let gates = gates(&["x00 OR y00 -> z00"]);
+
+        let expected = "x00 y00 | z00
+  0   0 | 0
+  0   1 | 1
+  1   0 | 1
+  1   1 | 1";
+
+        assert_eq!(truth_table(&gates, "z00").unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn test_truth_table_errors() {
+        let cyclic = gates(&[
+            "x00 AND ccc -> aaa",
+            "aaa OR y00 -> bbb",
+            "bbb XOR x00 -> ccc",
+        ]);
+
+        let wide_lines: Vec<String> = (0..MAX_CONE_INPUTS)
+            .map(|bit| format!("w{bit:02} OR x{bit:02} -> w{:02}", bit + 1))
+            .collect();
+        let wide: Vec<Gate> = wide_lines.iter().map(Gate::from).collect();
+
+        assert_eq!(
+            truth_table(&cyclic, "zzz"),
+            Err(TruthTableError::UnknownWire(String::from("zzz")))
+        );
+        assert_eq!(
+            truth_table(&cyclic, "bbb"),
+            Err(TruthTableError::Cycle(String::from("bbb")))
+        );
+        assert_eq!(
+            truth_table(&wide, "w20"),
+            Err(TruthTableError::ConeTooLarge {
+                wire: String::from("w20"),
+                inputs: 21,
+            })
+        );
+        assert!(truth_table(&wide, "w05").is_ok());
+    }
+
+    #[test]
+    fn test_compare() {
+        let left = gates(&[
+            "x00 XOR y00 -> z00",
+            "x00 AND y00 -> z01",
+            "x01 OR y01 -> z02",
+        ]);
+        let right = gates(&[
+            "y00 AND x00 -> carry",
+            "carry OR carry -> z01",
+            "y00 XOR x00 -> z00",
+            "x01 XOR y01 -> z02",
+            "x01 AND y01 -> z03",
+        ]);
+
+        let expected = EquivalenceReport {
+            differing_wires: vec![String::from("z02"), String::from("z03")],
+            unchecked_wires: Vec::new(),
+        };
+
+        let result = compare(&left, &right).unwrap();
+
+        assert_eq!(result, expected);
+        assert!(!result.is_equivalent());
+        assert!(compare(&left, &left).unwrap().is_equivalent());
+    }
+}
@@ -3,31 +3,46 @@ mod safety_manual;
 use crate::util::file_reader::to_string_vector;
 use std::collections::HashSet;
 
-use safety_manual::{PageOrderingRuleLine, PageOrderingRules};
+use safety_manual::{OrderingError, PageOrderingRules, SafetyManual, Update, UpdateReport};
 
 pub fn run() {
     let input = to_string_vector("inputs/day_5.txt").expect("Something went wrong with Day 5!");
 
-    let (rules, pages_to_print) = parse_data(&input);
+    let manual = parse_data(&input);
 
-    println!("Day 5 Part 1: {:?}", part_1(&rules, &pages_to_print));
-    println!("Day 5 Part 2: {:?}", part_2(&rules, &pages_to_print));
+    for problem in manual.problems() {
+        println!("Day 5 Problem: {problem}");
+    }
+
+    println!("Day 5 Part 1: {:?}", part_1(&manual));
+    println!("Day 5 Part 2: {:?}", part_2(&manual));
+    println!("Day 5 Audit: {}", audit_summary(&manual));
 }
 
-fn part_1(rules: &PageOrderingRules, pages_to_print: &[Vec<u32>]) -> u32 {
-    pages_to_print
+/// Updates without a middle page are skipped, and reported by [`SafetyManual::problems`].
+fn part_1(manual: &SafetyManual) -> u32 {
+    manual
+        .updates
         .iter()
-        .filter(|line| pages_to_print_line_is_valid(rules, line))
-        .map(|line| line.get(line.len() / 2).unwrap())
+        .filter(|update| pages_to_print_line_is_valid(&manual.rules, &update.pages))
+        .filter_map(|update| update.middle_page().ok())
         .sum()
 }
 
-fn part_2(rules: &PageOrderingRules, pages_to_print: &[Vec<u32>]) -> u32 {
-    pages_to_print
+/// Updates without a middle page are skipped, like in [`part_1`], and so are updates whose
+/// rules form a cycle, which [`SafetyManual::problems`] reports too.
+fn part_2(manual: &SafetyManual) -> u32 {
+    manual
+        .updates
         .iter()
-        .filter(|line| !pages_to_print_line_is_valid(rules, line))
-        .map(|line| fix_line(rules, line))
-        .map(|line| *line.get(line.len() / 2).unwrap())
+        .filter(|update| !pages_to_print_line_is_valid(&manual.rules, &update.pages))
+        .filter_map(|update| {
+            Some(Update {
+                line: update.line,
+                pages: fix_line(&manual.rules, &update.pages).ok()?,
+            })
+        })
+        .filter_map(|update| update.middle_page().ok())
         .sum()
 }
 
@@ -48,96 +63,97 @@ fn pages_to_print_line_is_valid(rules: &PageOrderingRules, line: &[u32]) -> bool
     valid_so_far
 }
 
-fn fix_line(rules: &PageOrderingRules, line: &[u32]) -> Vec<u32> {
-    let mut result = line.to_vec();
-
-    loop {
-        let mut current_index = 0;
-        let mut conflicts = HashSet::new();
-        let mut pages_so_far = HashSet::new();
-
-        for &page_number in &result {
-            conflicts = rules.conflicting_pages(page_number, &pages_so_far);
-
-            if !conflicts.is_empty() {
-                break;
-            }
-
-            current_index += 1;
-            pages_so_far.insert(page_number);
-        }
-
-        if conflicts.is_empty() {
-            break;
-        }
-
-        let conflict_with_lowest_index = result
-            .iter()
-            .position(|number| conflicts.contains(number))
-            .unwrap();
-
-        let problem_page_number = result.remove(current_index);
-
-        result.insert(conflict_with_lowest_index, problem_page_number);
-    }
-
-    result
-}
-
-fn parse_data(input: &[String]) -> (PageOrderingRules, Vec<Vec<u32>>) {
-    let mut split_data = input.split(String::is_empty);
-
-    let rules_data = split_data.next().expect("No rules data!");
-    let pages_data = split_data.next().expect("No rules data!");
-
-    (parse_rules(rules_data), parse_pages_to_print(pages_data))
+/// Explains every update that breaks a rule, along with the line it's on.
+fn explain_invalid_updates(manual: &SafetyManual) -> Vec<(usize, UpdateReport)> {
+    manual
+        .updates
+        .iter()
+        .map(|update| (update.line, manual.rules.explain(&update.pages)))
+        .filter(|(_, report)| !report.is_valid())
+        .collect()
 }
 
-fn parse_rules(rules: &[String]) -> PageOrderingRules {
-    let mut result = PageOrderingRules::new();
+/// Totals up [`explain_invalid_updates`]: how many updates break rules, how many times, and
+/// how many pages have to move to fix the ones that can be fixed.
+fn audit_summary(manual: &SafetyManual) -> String {
+    let reports = explain_invalid_updates(manual);
 
-    for rule_line in parse_rule_lines(rules) {
-        result.add_rule_from_line(rule_line);
-    }
+    let violations: usize = reports
+        .iter()
+        .map(|(_, report)| report.violations.len())
+        .sum();
+    let moves: usize = reports
+        .iter()
+        .filter_map(|(_, report)| report.minimal_moves)
+        .sum();
 
-    result
+    format!(
+        "{} invalid updates with {violations} rule violations, fixed by moving {moves} pages",
+        reports.len()
+    )
 }
 
-fn parse_rule_lines(rules: &[String]) -> Vec<PageOrderingRuleLine> {
-    rules.iter().map(|line| line.parse().unwrap()).collect()
+fn fix_line(rules: &PageOrderingRules, line: &[u32]) -> Result<Vec<u32>, OrderingError> {
+    rules.order(line)
 }
 
-fn parse_pages_to_print(lines: &[String]) -> Vec<Vec<u32>> {
-    lines
-        .iter()
-        .map(|line| {
-            line.split(',')
-                .map(|number| number.parse().unwrap())
-                .collect()
-        })
-        .collect()
+fn parse_data(input: &[String]) -> SafetyManual {
+    SafetyManual::try_from(input).unwrap_or_else(|error| panic!("Invalid safety manual, {error}!"))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::safety_manual::parse_rules;
     use super::*;
 
     #[test]
     fn test_part_1() {
         let input = to_string_vector("test_inputs/day_5.txt").unwrap();
 
-        let (rules, pages_to_print) = parse_data(&input);
+        let manual = parse_data(&input);
 
-        assert_eq!(part_1(&rules, &pages_to_print), 143);
+        assert_eq!(part_1(&manual), 143);
     }
 
     #[test]
     fn test_part_2() {
         let input = to_string_vector("test_inputs/day_5.txt").unwrap();
 
-        let (rules, pages_to_print) = parse_data(&input);
+        let manual = parse_data(&input);
+
+        assert_eq!(part_2(&manual), 123);
+    }
+
+    #[test]
+    fn test_parts_skip_updates_without_middle_page() {
+        let input: Vec<String> = ["47|53", "", "47,53,1", "53,47", "53,47,2"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let manual = parse_data(&input);
 
-        assert_eq!(part_2(&rules, &pages_to_print), 123);
+        assert_eq!(manual.problems().len(), 1);
+        assert_eq!(part_1(&manual), 53);
+        assert_eq!(part_2(&manual), 53);
+    }
+
+    #[test]
+    fn test_part_2_skips_updates_with_cyclic_rules() {
+        let input: Vec<String> = ["1|2", "2|1", "3|4", "", "1,2,5", "4,3,6"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let manual = parse_data(&input);
+
+        assert_eq!(
+            manual.problems().last().map(ToString::to_string),
+            Some(String::from(
+                "line 5: update can't be put in order, rules 1|2, 2|1 form a cycle"
+            ))
+        );
+        assert_eq!(part_2(&manual), 4);
     }
 
     #[test]
@@ -208,6 +224,35 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn test_explain_invalid_updates() {
+        let input = to_string_vector("test_inputs/day_5.txt").unwrap();
+
+        let manual = parse_data(&input);
+
+        let result: Vec<(usize, usize, Option<usize>)> = explain_invalid_updates(&manual)
+            .into_iter()
+            .map(|(line, report)| (line, report.violations.len(), report.minimal_moves))
+            .collect();
+
+        assert_eq!(
+            result,
+            [(26, 1, Some(1)), (27, 1, Some(1)), (28, 4, Some(2))]
+        );
+    }
+
+    #[test]
+    fn test_audit_summary() {
+        let input = to_string_vector("test_inputs/day_5.txt").unwrap();
+
+        let manual = parse_data(&input);
+
+        assert_eq!(
+            audit_summary(&manual),
+            "3 invalid updates with 6 rule violations, fixed by moving 4 pages"
+        );
+    }
+
     #[test]
     fn test_fix_line() {
         let input_rule_lines = [
@@ -237,92 +282,10 @@ mod tests {
         let input_rules = parse_rules(&input_rule_lines);
         let input_pages_to_print_line = [75, 61, 13, 97, 29];
 
-        let expected = vec![97, 75, 61, 29, 13];
+        let expected = Ok(vec![97, 75, 61, 29, 13]);
 
         let result = fix_line(&input_rules, &input_pages_to_print_line);
 
         assert_eq!(result, expected);
     }
-
-    #[test]
-    fn test_parse_rule_lines() {
-        let input = [
-            String::from("47|53"),
-            String::from("97|13"),
-            String::from("97|61"),
-            String::from("97|47"),
-        ];
-
-        let expected = vec![
-            PageOrderingRuleLine::new(47, 53),
-            PageOrderingRuleLine::new(97, 13),
-            PageOrderingRuleLine::new(97, 61),
-            PageOrderingRuleLine::new(97, 47),
-        ];
-
-        let result = parse_rule_lines(&input);
-
-        assert_eq!(result, expected);
-    }
-
-    #[test]
-    fn test_parse_rules() {
-        let input = [
-            String::from("47|53"),
-            String::from("97|13"),
-            String::from("97|61"),
-            String::from("97|47"),
-        ];
-
-        let mut expected = PageOrderingRules::new();
-
-        expected.add_rule_from_line(PageOrderingRuleLine::new(47, 53));
-        expected.add_rule_from_line(PageOrderingRuleLine::new(97, 13));
-        expected.add_rule_from_line(PageOrderingRuleLine::new(97, 61));
-        expected.add_rule_from_line(PageOrderingRuleLine::new(97, 47));
-
-        let result = parse_rules(&input);
-
-        assert_eq!(result, expected);
-    }
-
-    #[test]
-    fn test_parse_pages_to_print() {
-        let input = [
-            String::from("75,47,61,53,29"),
-            String::from("97,61,53,29,13"),
-        ];
-
-        let expected = vec![vec![75, 47, 61, 53, 29], vec![97, 61, 53, 29, 13]];
-
-        let result = parse_pages_to_print(&input);
-
-        assert_eq!(result, expected);
-    }
-
-    #[test]
-    fn test_parse_data() {
-        let input = [
-            String::from("47|53"),
-            String::from("97|13"),
-            String::from("97|61"),
-            String::from("97|47"),
-            String::new(),
-            String::from("75,47,61,53,29"),
-            String::from("97,61,53,29,13"),
-        ];
-
-        let mut expected_rules = PageOrderingRules::new();
-
-        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(47, 53));
-        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(97, 13));
-        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(97, 61));
-        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(97, 47));
-
-        let expected_pages = vec![vec![75, 47, 61, 53, 29], vec![97, 61, 53, 29, 13]];
-
-        let result = parse_data(&input);
-
-        assert_eq!(result, (expected_rules, expected_pages));
-    }
 }
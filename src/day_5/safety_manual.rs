@@ -1,6 +1,245 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::str::FromStr;
 
+use crate::util::graph;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OrderingError {
+    /// Holds the rules between the pages of the update that form cycles, sorted.
+    Cycle(Vec<PageOrderingRuleLine>),
+}
+
+impl fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderingError::Cycle(rules) => {
+                let rules: Vec<String> = rules.iter().map(ToString::to_string).collect();
+
+                write!(f, "rules {} form a cycle", rules.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SafetyManualError {
+    MissingUpdates,
+    InvalidRule { line: usize, text: String },
+    InvalidPage { line: usize, page: String },
+    EmptyUpdate { line: usize },
+    DuplicatePage { line: usize, page: u32 },
+    UnknownPage { line: usize, page: u32 },
+    EvenLengthUpdate { line: usize, length: usize },
+    UnorderableUpdate { line: usize, error: OrderingError },
+}
+
+impl fmt::Display for SafetyManualError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SafetyManualError::MissingUpdates => {
+                write!(f, "no blank line between the rules and the updates")
+            }
+            SafetyManualError::InvalidRule { line, text } => {
+                write!(f, "line {line}: `{text}` isn't a rule like `47|53`")
+            }
+            SafetyManualError::InvalidPage { line, page } => {
+                write!(f, "line {line}: `{page}` isn't a page number")
+            }
+            SafetyManualError::EmptyUpdate { line } => write!(f, "line {line}: update is empty"),
+            SafetyManualError::DuplicatePage { line, page } => {
+                write!(f, "line {line}: page {page} is printed more than once")
+            }
+            SafetyManualError::UnknownPage { line, page } => {
+                write!(f, "line {line}: page {page} isn't in any update")
+            }
+            SafetyManualError::EvenLengthUpdate { line, length } => {
+                write!(
+                    f,
+                    "line {line}: update has {length} pages, so no middle page"
+                )
+            }
+            SafetyManualError::UnorderableUpdate { line, error } => {
+                write!(f, "line {line}: update can't be put in order, {error}")
+            }
+        }
+    }
+}
+
+/// The pages of one update, and the line of the manual they're on, counting from 1.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Update {
+    pub line: usize,
+    pub pages: Vec<u32>,
+}
+
+impl Update {
+    /// # Errors
+    ///
+    /// If the update has an even number of pages.
+    pub fn middle_page(&self) -> Result<u32, SafetyManualError> {
+        if self.pages.len().is_multiple_of(2) {
+            return Err(SafetyManualError::EvenLengthUpdate {
+                line: self.line,
+                length: self.pages.len(),
+            });
+        }
+
+        Ok(self.pages[self.pages.len() / 2])
+    }
+}
+
+/// The rules and updates of the safety manual, parsed from the puzzle input.
+#[derive(Debug, PartialEq)]
+pub struct SafetyManual {
+    pub rules: PageOrderingRules,
+    pub updates: Vec<Update>,
+    rule_lines: Vec<(usize, PageOrderingRuleLine)>,
+}
+
+impl SafetyManual {
+    /// Problems that don't stop the manual being read, but make some answers meaningless:
+    /// rules about pages no update prints, updates without a middle page, and updates whose
+    /// rules form a cycle.
+    pub fn problems(&self) -> Vec<SafetyManualError> {
+        let printed: HashSet<u32> = self
+            .updates
+            .iter()
+            .flat_map(|update| update.pages.iter().copied())
+            .collect();
+
+        let unknown_pages = self.rule_lines.iter().flat_map(|(line, rule)| {
+            [rule.before_page_number, rule.after_page_number]
+                .into_iter()
+                .filter(|page| !printed.contains(page))
+                .map(|page| SafetyManualError::UnknownPage { line: *line, page })
+        });
+
+        let even_lengths = self
+            .updates
+            .iter()
+            .filter_map(|update| update.middle_page().err());
+
+        let unorderable = self.updates.iter().filter_map(|update| {
+            self.rules.order(&update.pages).err().map(|error| {
+                SafetyManualError::UnorderableUpdate {
+                    line: update.line,
+                    error,
+                }
+            })
+        });
+
+        unknown_pages
+            .chain(even_lengths)
+            .chain(unorderable)
+            .collect()
+    }
+
+    fn parse_update(line: usize, text: &str) -> Result<Update, SafetyManualError> {
+        if text.is_empty() {
+            return Err(SafetyManualError::EmptyUpdate { line });
+        }
+
+        let mut seen = HashSet::new();
+        let mut pages = Vec::new();
+
+        for page in text.split(',') {
+            let page: u32 = page
+                .trim()
+                .parse()
+                .map_err(|_| SafetyManualError::InvalidPage {
+                    line,
+                    page: page.to_string(),
+                })?;
+
+            if !seen.insert(page) {
+                return Err(SafetyManualError::DuplicatePage { line, page });
+            }
+
+            pages.push(page);
+        }
+
+        Ok(Update { line, pages })
+    }
+}
+
+impl TryFrom<&[String]> for SafetyManual {
+    type Error = SafetyManualError;
+
+    fn try_from(input: &[String]) -> Result<Self, Self::Error> {
+        let blank_line = input
+            .iter()
+            .position(String::is_empty)
+            .ok_or(SafetyManualError::MissingUpdates)?;
+
+        let mut rules = PageOrderingRules::new();
+        let mut rule_lines = Vec::new();
+
+        for (index, text) in input[..blank_line].iter().enumerate() {
+            let line = index + 1;
+
+            let rule: PageOrderingRuleLine =
+                text.trim()
+                    .parse()
+                    .map_err(|_| SafetyManualError::InvalidRule {
+                        line,
+                        text: text.clone(),
+                    })?;
+
+            rules.add_rule_from_line(rule);
+            rule_lines.push((line, rule));
+        }
+
+        let updates = input
+            .iter()
+            .enumerate()
+            .skip(blank_line + 1)
+            .filter(|(index, text)| !text.is_empty() || *index + 1 < input.len())
+            .map(|(index, text)| SafetyManual::parse_update(index + 1, text))
+            .collect::<Result<Vec<Update>, SafetyManualError>>()?;
+
+        Ok(SafetyManual {
+            rules,
+            updates,
+            rule_lines,
+        })
+    }
+}
+
+/// A pair of pages printed in the wrong order: the page at `earlier_position` has to come
+/// after the page at `later_position` because of `rule`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RuleViolation {
+    pub earlier_position: usize,
+    pub later_position: usize,
+    pub rule: PageOrderingRuleLine,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is printed before {}, breaking rule {}",
+            self.rule.after_page_number, self.rule.before_page_number, self.rule
+        )
+    }
+}
+
+/// Why an update is or isn't in the right order.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct UpdateReport {
+    pub violations: Vec<RuleViolation>,
+    /// The fewest pages that have to be moved to put the update in order, or `None` if the
+    /// rules between its pages form a cycle.
+    pub minimal_moves: Option<usize>,
+}
+
+impl UpdateReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct PageOrderingRules {
     rules: HashMap<u32, PageOrderingRule>,
@@ -26,11 +265,160 @@ impl PageOrderingRules {
             .is_none_or(|rule| !rule.contains_after_pages_in(pages_before))
     }
 
-    pub fn conflicting_pages(&self, page_number: u32, pages_before: &HashSet<u32>) -> HashSet<u32> {
-        self.rules.get(&page_number).map_or(HashSet::new(), |rule| {
-            rule.after_pages_contained_in(pages_before)
-        })
+    pub fn has_rule(&self, before_page_number: u32, after_page_number: u32) -> bool {
+        self.rules
+            .get(&before_page_number)
+            .is_some_and(|rule| rule.pages_after.contains(&after_page_number))
+    }
+
+    /// Reorders `pages` so no rule between them is broken, with a topological sort of the
+    /// rules that only mention pages in the update. Whenever several pages could come next,
+    /// the one with the lowest original position goes first, so a valid update comes back
+    /// unchanged. A page printed twice is ordered as two separate pages.
+    pub fn order(&self, pages: &[u32]) -> Result<Vec<u32>, OrderingError> {
+        let adjacency = self.rules_adjacency(pages);
+
+        if let Ok(order) = graph::topological_order(&adjacency) {
+            return Ok(order.into_iter().map(|position| pages[position]).collect());
+        }
+
+        let mut cycle_rules = Vec::new();
+
+        for component in graph::strongly_connected_components(&adjacency) {
+            for before in &component {
+                for after in &component {
+                    if adjacency[*before].contains(after) {
+                        cycle_rules.push(PageOrderingRuleLine::new(pages[*before], pages[*after]));
+                    }
+                }
+            }
+        }
+
+        cycle_rules.sort_unstable();
+        cycle_rules.dedup();
+
+        Err(OrderingError::Cycle(cycle_rules))
+    }
+
+    /// Lists every pair of pages in `pages` that breaks a rule, and how many pages would have
+    /// to move to fix them all.
+    pub fn explain(&self, pages: &[u32]) -> UpdateReport {
+        let mut violations = Vec::new();
+
+        for (earlier_position, earlier_page) in pages.iter().enumerate() {
+            for (later_position, later_page) in pages.iter().enumerate().skip(earlier_position + 1)
+            {
+                if self.has_rule(*later_page, *earlier_page) {
+                    violations.push(RuleViolation {
+                        earlier_position,
+                        later_position,
+                        rule: PageOrderingRuleLine::new(*later_page, *earlier_page),
+                    });
+                }
+            }
+        }
+
+        UpdateReport {
+            violations,
+            minimal_moves: self.minimal_moves(pages),
+        }
     }
+
+    /// The pages that stay put must already be in order, even counting rules that only hold
+    /// through pages that move. Pairs of positions that are out of order that way form a
+    /// partial order, so the most pages that can stay is its largest antichain, and by
+    /// Dilworth's theorem the pages that have to move match its largest bipartite matching.
+    fn minimal_moves(&self, pages: &[u32]) -> Option<usize> {
+        let mut precedes: Vec<Vec<bool>> = pages
+            .iter()
+            .map(|before| {
+                pages
+                    .iter()
+                    .map(|after| self.has_rule(*before, *after))
+                    .collect()
+            })
+            .collect();
+
+        for via in 0..pages.len() {
+            let via_row = precedes[via].clone();
+
+            for row in &mut precedes {
+                if row[via] {
+                    for (precedes, via_precedes) in row.iter_mut().zip(&via_row) {
+                        *precedes |= via_precedes;
+                    }
+                }
+            }
+        }
+
+        if precedes.iter().enumerate().any(|(page, row)| row[page]) {
+            return None;
+        }
+
+        let out_of_order: Vec<Vec<usize>> = (0..pages.len())
+            .map(|earlier| {
+                ((earlier + 1)..pages.len())
+                    .filter(|later| precedes[*later][earlier])
+                    .collect()
+            })
+            .collect();
+
+        let mut matches = vec![None; pages.len()];
+
+        Some(
+            (0..pages.len())
+                .filter(|earlier| {
+                    augment(
+                        &out_of_order,
+                        *earlier,
+                        &mut vec![false; pages.len()],
+                        &mut matches,
+                    )
+                })
+                .count(),
+        )
+    }
+
+    /// Lists, for each position in `pages`, the positions of the pages a rule says have to
+    /// come after it.
+    fn rules_adjacency(&self, pages: &[u32]) -> Vec<Vec<usize>> {
+        pages
+            .iter()
+            .map(|before_page| {
+                pages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, after_page)| self.has_rule(*before_page, **after_page))
+                    .map(|(after, _)| after)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Looks for an augmenting path from `left` with Kuhn's algorithm, updating `matches` (which
+/// maps each right node to its left node) if there is one.
+fn augment(
+    edges: &[Vec<usize>],
+    left: usize,
+    visited: &mut [bool],
+    matches: &mut [Option<usize>],
+) -> bool {
+    for right in &edges[left] {
+        if visited[*right] {
+            continue;
+        }
+
+        visited[*right] = true;
+
+        if matches[*right].is_none_or(|other| augment(edges, other, visited, matches)) {
+            matches[*right] = Some(left);
+
+            return true;
+        }
+    }
+
+    false
 }
 
 #[derive(Debug, PartialEq)]
@@ -54,13 +442,6 @@ impl PageOrderingRule {
     pub fn contains_after_pages_in(&self, page_numbers: &HashSet<u32>) -> bool {
         !self.pages_after.is_disjoint(page_numbers)
     }
-
-    pub fn after_pages_contained_in(&self, page_numbers: &HashSet<u32>) -> HashSet<u32> {
-        self.pages_after
-            .intersection(page_numbers)
-            .copied()
-            .collect()
-    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -77,7 +458,7 @@ impl FromStr for PageOrderingRule {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub struct PageOrderingRuleLine {
     before_page_number: u32,
     after_page_number: u32,
@@ -92,13 +473,19 @@ impl PageOrderingRuleLine {
     }
 
     fn from_str_parts(before: &str, after: &str) -> Result<Self, ParsePageOrderingRuleLineError> {
-        match (before.parse(), after.parse()) {
+        match (before.trim().parse(), after.trim().parse()) {
             (Ok(before_num), Ok(after_num)) => Ok(PageOrderingRuleLine::new(before_num, after_num)),
             _ => Err(ParsePageOrderingRuleLineError),
         }
     }
 }
 
+impl fmt::Display for PageOrderingRuleLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.before_page_number, self.after_page_number)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParsePageOrderingRuleLineError;
 
@@ -112,6 +499,22 @@ impl FromStr for PageOrderingRuleLine {
     }
 }
 
+/// Builds rules from lines like `47|53`, for tests.
+///
+/// # Panics
+///
+/// If a line isn't a rule.
+#[cfg(test)]
+pub fn parse_rules<S: AsRef<str>>(lines: &[S]) -> PageOrderingRules {
+    let mut rules = PageOrderingRules::new();
+
+    for line in lines {
+        rules.add_rule_from_line(line.as_ref().parse().unwrap());
+    }
+
+    rules
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +541,228 @@ mod tests {
         assert_eq!(result_ok, expected_ok);
         assert_eq!(result_err, expected_err);
     }
+
+    #[test]
+    fn test_order() {
+        let rules = parse_rules(&["97|75", "75|13", "29|13", "61|29", "75|61", "97|61"]);
+
+        assert_eq!(
+            rules.order(&[75, 61, 13, 97, 29]),
+            Ok(vec![97, 75, 61, 29, 13])
+        );
+        assert_eq!(rules.order(&[97, 75, 61]), Ok(vec![97, 75, 61]));
+        assert_eq!(rules.order(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_order_keeps_unordered_pages_in_place() {
+        let rules = parse_rules(&["3|1"]);
+
+        assert_eq!(rules.order(&[5, 1, 4, 3, 2]), Ok(vec![5, 4, 3, 1, 2]));
+    }
+
+    #[test]
+    fn test_order_repeated_pages() {
+        let rules = parse_rules(&["1|2", "3|1"]);
+
+        assert_eq!(rules.order(&[2, 1, 2, 3]), Ok(vec![3, 1, 2, 2]));
+    }
+
+    #[test]
+    fn test_order_cycle() {
+        let rules = parse_rules(&["1|2", "2|3", "3|1", "3|4", "4|5"]);
+
+        let expected = OrderingError::Cycle(vec![
+            PageOrderingRuleLine::new(1, 2),
+            PageOrderingRuleLine::new(2, 3),
+            PageOrderingRuleLine::new(3, 1),
+        ]);
+
+        let result = rules.order(&[4, 3, 2, 1, 5]);
+
+        assert_eq!(result, Err(expected));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "rules 1|2, 2|3, 3|1 form a cycle"
+        );
+        assert_eq!(rules.order(&[4, 3, 5]), Ok(vec![3, 4, 5]));
+    }
+
+    #[test]
+    fn test_explain() {
+        let rules = parse_rules(&[
+            "47|53", "97|13", "97|61", "97|47", "75|29", "61|13", "75|53", "29|13", "97|29",
+            "53|29", "61|53", "97|53", "61|29", "47|13", "75|47", "97|75", "47|61", "75|61",
+            "47|29", "75|13", "53|13",
+        ]);
+
+        let violation = |earlier_position, later_position, rule: &str| RuleViolation {
+            earlier_position,
+            later_position,
+            rule: rule.parse().unwrap(),
+        };
+
+        let expected = UpdateReport {
+            violations: vec![
+                violation(1, 2, "75|13"),
+                violation(1, 3, "29|13"),
+                violation(1, 4, "47|13"),
+                violation(3, 4, "47|29"),
+            ],
+            minimal_moves: Some(2),
+        };
+
+        let result = rules.explain(&[97, 13, 75, 29, 47]);
+
+        assert_eq!(result, expected);
+        assert!(!result.is_valid());
+        assert_eq!(
+            result.violations[0].to_string(),
+            "13 is printed before 75, breaking rule 75|13"
+        );
+
+        let valid = rules.explain(&[75, 47, 61, 53, 29]);
+
+        assert!(valid.is_valid());
+        assert_eq!(valid.minimal_moves, Some(0));
+        assert_eq!(rules.explain(&[75, 97, 47, 61, 53]).minimal_moves, Some(1));
+    }
+
+    #[test]
+    fn test_explain_minimal_moves_through_other_pages() {
+        // 3 and 1 aren't ordered directly, but 1 has to come before 2 and 2 before 3, so
+        // they can't both stay put.
+        let rules = parse_rules(&["1|2", "2|3"]);
+
+        assert_eq!(rules.explain(&[3, 1, 2]).minimal_moves, Some(1));
+        assert_eq!(rules.explain(&[3, 2, 1]).minimal_moves, Some(2));
+        assert_eq!(rules.explain(&[2, 5, 3, 4, 1]).minimal_moves, Some(1));
+        assert_eq!(rules.explain(&[1, 2, 3]).minimal_moves, Some(0));
+    }
+
+    #[test]
+    fn test_explain_cycle() {
+        let rules = parse_rules(&["1|2", "2|1"]);
+
+        let expected = UpdateReport {
+            violations: vec![RuleViolation {
+                earlier_position: 0,
+                later_position: 1,
+                rule: PageOrderingRuleLine::new(2, 1),
+            }],
+            minimal_moves: None,
+        };
+
+        assert_eq!(rules.explain(&[1, 2]), expected);
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_safety_manual_try_from() {
+        let input = lines("47|53\n97|13\n\n97,47,53\n13,97,53,47,61");
+
+        let mut expected_rules = PageOrderingRules::new();
+
+        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(47, 53));
+        expected_rules.add_rule_from_line(PageOrderingRuleLine::new(97, 13));
+
+        let expected = SafetyManual {
+            rules: expected_rules,
+            updates: vec![
+                Update {
+                    line: 4,
+                    pages: vec![97, 47, 53],
+                },
+                Update {
+                    line: 5,
+                    pages: vec![13, 97, 53, 47, 61],
+                },
+            ],
+            rule_lines: vec![
+                (1, PageOrderingRuleLine::new(47, 53)),
+                (2, PageOrderingRuleLine::new(97, 13)),
+            ],
+        };
+
+        let result = SafetyManual::try_from(input.as_slice());
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_safety_manual_try_from_trims_rules() {
+        let input = lines(" 47 | 53 \n97|13\t\n\n97,47,53");
+
+        let manual = SafetyManual::try_from(input.as_slice()).unwrap();
+
+        assert_eq!(
+            manual.rule_lines,
+            vec![
+                (1, PageOrderingRuleLine::new(47, 53)),
+                (2, PageOrderingRuleLine::new(97, 13)),
+            ]
+        );
+        assert!(manual.rules.has_rule(47, 53));
+    }
+
+    #[test]
+    fn test_safety_manual_try_from_errors() {
+        let cases = [
+            ("47|53\n97,47", SafetyManualError::MissingUpdates),
+            (
+                "47|53\n97-13\n\n97,47,53",
+                SafetyManualError::InvalidRule {
+                    line: 2,
+                    text: String::from("97-13"),
+                },
+            ),
+            (
+                "47|53\n\n47,53,1\n47,x,53",
+                SafetyManualError::InvalidPage {
+                    line: 4,
+                    page: String::from("x"),
+                },
+            ),
+            (
+                "47|53\n\n47,53,1\n\n1,2,3",
+                SafetyManualError::EmptyUpdate { line: 4 },
+            ),
+            (
+                "47|53\n\n47,53,47",
+                SafetyManualError::DuplicatePage { line: 3, page: 47 },
+            ),
+        ];
+
+        for (text, expected) in cases {
+            let input = lines(text);
+
+            assert_eq!(
+                SafetyManual::try_from(input.as_slice()),
+                Err(expected),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_safety_manual_problems() {
+        let input = lines("47|53\n97|13\n75|47\n\n97,47,53\n75,47\n47,53,97");
+
+        let manual = SafetyManual::try_from(input.as_slice()).unwrap();
+
+        let expected = vec![
+            SafetyManualError::UnknownPage { line: 2, page: 13 },
+            SafetyManualError::EvenLengthUpdate { line: 6, length: 2 },
+        ];
+
+        assert_eq!(manual.problems(), expected);
+        assert_eq!(manual.updates[0].middle_page(), Ok(47));
+        assert_eq!(
+            manual.updates[1].middle_page().unwrap_err().to_string(),
+            "line 6: update has 2 pages, so no middle page"
+        );
+    }
 }
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Finds `(mu, lambda)` for the sequence `start, next(start), next(next(start)), ...` with
+/// Floyd's tortoise and hare, where `mu` is the index of the first state in the cycle and
+/// `lambda` is the cycle length.
+///
+/// Only ever keeps two states around, but calls `next` roughly three times as often as
+/// [`hash_cycle`].
+pub fn floyd_cycle<S, F>(start: &S, next: F) -> (usize, usize)
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut tortoise = next(start);
+    let mut hare = next(&tortoise);
+
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&next(&hare));
+    }
+
+    let mut mu = 0;
+
+    tortoise = start.clone();
+
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+
+        mu += 1;
+    }
+
+    let mut lambda = 1;
+
+    hare = next(&tortoise);
+
+    while tortoise != hare {
+        hare = next(&hare);
+
+        lambda += 1;
+    }
+
+    (mu, lambda)
+}
+
+/// Finds `(mu, lambda)` for the sequence `start, next(start), next(next(start)), ...` with
+/// Brent's algorithm, where `mu` is the index of the first state in the cycle and `lambda`
+/// is the cycle length.
+///
+/// Like [`floyd_cycle`] it only keeps two states around, but usually calls `next` less.
+pub fn brent_cycle<S, F>(start: &S, next: F) -> (usize, usize)
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut power = 1;
+    let mut lambda = 1;
+    let mut tortoise = start.clone();
+    let mut hare = next(start);
+
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare.clone();
+            power *= 2;
+            lambda = 0;
+        }
+
+        hare = next(&hare);
+
+        lambda += 1;
+    }
+
+    tortoise = start.clone();
+    hare = start.clone();
+
+    for _ in 0..lambda {
+        hare = next(&hare);
+    }
+
+    let mut mu = 0;
+
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+
+        mu += 1;
+    }
+
+    (mu, lambda)
+}
+
+/// Finds `(mu, lambda)` for the sequence `start, next(start), next(next(start)), ...` by
+/// remembering the step every state was first seen at, where `mu` is the index of the first
+/// state in the cycle and `lambda` is the cycle length.
+///
+/// Calls `next` exactly `mu + lambda` times, at the cost of storing every state.
+pub fn hash_cycle<S, F>(start: &S, next: F) -> (usize, usize)
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> S,
+{
+    let mut first_seen_at = HashMap::new();
+    let mut current = start.clone();
+    let mut step = 0;
+
+    loop {
+        if let Some(mu) = first_seen_at.get(&current) {
+            return (*mu, step - mu);
+        }
+
+        let following = next(&current);
+
+        first_seen_at.insert(current, step);
+
+        current = following;
+        step += 1;
+    }
+}
+
+/// Maps `step` to the earliest step with the same state, given the `(mu, lambda)` of the
+/// sequence.
+///
+/// # Panics
+///
+/// If `lambda` is zero.
+#[must_use]
+pub fn equivalent_step(step: usize, (mu, lambda): (usize, usize)) -> usize {
+    assert!(lambda > 0, "Cycle length must be positive!");
+
+    if step < mu {
+        step
+    } else {
+        mu + (step - mu) % lambda
+    }
+}
+
+/// Returns the state after `step` calls to `next`, skipping whole laps of the cycle
+/// described by `(mu, lambda)`.
+///
+/// # Panics
+///
+/// If `lambda` is zero.
+pub fn state_at_step<S, F>(start: &S, next: F, step: usize, cycle: (usize, usize)) -> S
+where
+    S: Clone,
+    F: Fn(&S) -> S,
+{
+    let mut current = start.clone();
+
+    for _ in 0..equivalent_step(step, cycle) {
+        current = next(&current);
+    }
+
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 5, 26, 677 (= 2), ...
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn squares_plus_one(value: &u64) -> u64 {
+        (value * value + 1) % 675
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn rho(value: &u32) -> u32 {
+        if *value < 9 {
+            value + 1
+        } else {
+            4
+        }
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn squares_plus_three(value: &u32) -> u32 {
+        (value * value + 3) % 31
+    }
+
+    #[test]
+    fn test_floyd_cycle() {
+        assert_eq!(floyd_cycle(&0, rho), (4, 6));
+        assert_eq!(floyd_cycle(&4, rho), (0, 6));
+        assert_eq!(floyd_cycle(&0, squares_plus_one), (2, 3));
+    }
+
+    #[test]
+    fn test_brent_cycle() {
+        assert_eq!(brent_cycle(&0, rho), (4, 6));
+        assert_eq!(brent_cycle(&4, rho), (0, 6));
+        assert_eq!(brent_cycle(&0, squares_plus_one), (2, 3));
+    }
+
+    #[test]
+    fn test_hash_cycle() {
+        assert_eq!(hash_cycle(&0, rho), (4, 6));
+        assert_eq!(hash_cycle(&4, rho), (0, 6));
+        assert_eq!(hash_cycle(&0, squares_plus_one), (2, 3));
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let identity = |value: &u32| *value;
+
+        assert_eq!(floyd_cycle(&7, identity), (0, 1));
+        assert_eq!(brent_cycle(&7, identity), (0, 1));
+        assert_eq!(hash_cycle(&7, identity), (0, 1));
+    }
+
+    #[test]
+    fn test_detectors_agree() {
+        for start in 0..31 {
+            let expected = hash_cycle(&start, squares_plus_three);
+
+            assert_eq!(floyd_cycle(&start, squares_plus_three), expected);
+            assert_eq!(brent_cycle(&start, squares_plus_three), expected);
+        }
+    }
+
+    #[test]
+    fn test_equivalent_step() {
+        assert_eq!(equivalent_step(3, (4, 6)), 3);
+        assert_eq!(equivalent_step(4, (4, 6)), 4);
+        assert_eq!(equivalent_step(10, (4, 6)), 4);
+        assert_eq!(equivalent_step(1_000_000_000_003, (4, 6)), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle length must be positive!")]
+    fn test_equivalent_step_zero_length() {
+        let _ = equivalent_step(3, (0, 0));
+    }
+
+    #[test]
+    fn test_state_at_step() {
+        let cycle = brent_cycle(&0, rho);
+
+        assert_eq!(state_at_step(&0, rho, 2, cycle), 2);
+        assert_eq!(state_at_step(&0, rho, 10, cycle), 4);
+        assert_eq!(state_at_step(&0, rho, 1_000_000_000_003, cycle), 7);
+    }
+}
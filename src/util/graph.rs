@@ -0,0 +1,642 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::{self, Write};
+
+/// Hands out an index to every distinct name, in the order they're first seen, so graphs can
+/// work with `usize`s instead of strings.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct NodeTable {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl NodeTable {
+    #[must_use]
+    pub fn new() -> Self {
+        NodeTable::default()
+    }
+
+    /// Returns the index of `name`, giving it the next free one if it's new.
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(index) = self.indices.get(name) {
+            return *index;
+        }
+
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), self.names.len() - 1);
+
+        self.names.len() - 1
+    }
+
+    #[must_use]
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    /// # Panics
+    ///
+    /// If no name has `index`.
+    #[must_use]
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EdgeKind {
+    Directed,
+    Undirected,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GraphError {
+    /// Holds the nodes, in index order, that couldn't be ordered because they're on or after
+    /// a cycle.
+    Cycle(Vec<usize>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Cycle(nodes) => {
+                write!(f, "{} nodes are on or after a cycle", nodes.len())
+            }
+        }
+    }
+}
+
+/// An adjacency-list graph over named nodes. Undirected edges are stored in both directions,
+/// and adding an edge twice has no effect.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Graph {
+    kind: EdgeKind,
+    nodes: NodeTable,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    #[must_use]
+    pub fn new(kind: EdgeKind) -> Self {
+        Graph {
+            kind,
+            nodes: NodeTable::new(),
+            adjacency: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn directed() -> Self {
+        Graph::new(EdgeKind::Directed)
+    }
+
+    #[must_use]
+    pub fn undirected() -> Self {
+        Graph::new(EdgeKind::Undirected)
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> EdgeKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> &NodeTable {
+        &self.nodes
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Counts each undirected edge once.
+    #[must_use]
+    pub fn edge_count(&self) -> usize {
+        let stored: usize = self.adjacency.iter().map(Vec::len).sum();
+
+        match self.kind {
+            EdgeKind::Directed => stored,
+            EdgeKind::Undirected => {
+                let loops = (0..self.len())
+                    .filter(|node| self.adjacency[*node].contains(node))
+                    .count();
+
+                loops + (stored - loops) / 2
+            }
+        }
+    }
+
+    pub fn add_node(&mut self, name: &str) -> usize {
+        let index = self.nodes.intern(name);
+
+        if index == self.adjacency.len() {
+            self.adjacency.push(Vec::new());
+        }
+
+        index
+    }
+
+    /// Adds the nodes if they're new, and returns their indices.
+    pub fn add_edge(&mut self, from: &str, to: &str) -> (usize, usize) {
+        let (from, to) = (self.add_node(from), self.add_node(to));
+
+        self.add_edge_between(from, to);
+
+        (from, to)
+    }
+
+    /// Adds an edge between two nodes that are already in the graph, by index.
+    ///
+    /// # Panics
+    ///
+    /// If either node isn't in the graph.
+    pub fn add_edge_between(&mut self, from: usize, to: usize) {
+        assert!(
+            from < self.len() && to < self.len(),
+            "Both nodes must be in the graph!"
+        );
+
+        if !self.adjacency[from].contains(&to) {
+            self.adjacency[from].push(to);
+        }
+
+        if self.kind == EdgeKind::Undirected && !self.adjacency[to].contains(&from) {
+            self.adjacency[to].push(from);
+        }
+    }
+
+    /// The nodes `node` has an edge to, in the order the edges were added.
+    ///
+    /// # Panics
+    ///
+    /// If `node` isn't in the graph.
+    #[must_use]
+    pub fn neighbours(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    #[must_use]
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.adjacency
+            .get(from)
+            .is_some_and(|neighbours| neighbours.contains(&to))
+    }
+
+    /// Orders the nodes so that every edge goes forwards, with Kahn's algorithm. Whenever
+    /// several nodes are ready, the lowest index goes first, so the order is the same every
+    /// time.
+    ///
+    /// # Errors
+    ///
+    /// If the edges form a cycle. Every undirected edge counts as a cycle.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, GraphError> {
+        topological_order(&self.adjacency)
+    }
+
+    /// Splits the nodes into groups that can all reach each other, with Tarjan's algorithm.
+    /// Each group is sorted, and a group comes before every group that can reach it.
+    #[must_use]
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        strongly_connected_components(&self.adjacency)
+    }
+
+    /// The number of edges on the shortest path from `start` to every node, or `None` for
+    /// nodes it can't reach.
+    ///
+    /// # Panics
+    ///
+    /// If `start` isn't in the graph.
+    #[must_use]
+    pub fn bfs_distances(&self, start: usize) -> Vec<Option<usize>> {
+        self.bfs(start)
+            .into_iter()
+            .map(|visit| visit.map(|(distance, _)| distance))
+            .collect()
+    }
+
+    /// The nodes on a shortest path from `from` to `to`, both included. Ties go to the edges
+    /// that were added first.
+    ///
+    /// # Panics
+    ///
+    /// If `from` isn't in the graph.
+    #[must_use]
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let visits = self.bfs(from);
+
+        let mut path = vec![to];
+
+        while let Some((_, Some(previous))) = visits.get(*path.last()?).copied().flatten() {
+            path.push(previous);
+        }
+
+        if *path.last()? != from {
+            return None;
+        }
+
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// For every node reached from `start`, its distance and the node it was reached from.
+    fn bfs(&self, start: usize) -> Vec<Option<(usize, Option<usize>)>> {
+        let mut visits = vec![None; self.len()];
+        let mut queue = VecDeque::from([start]);
+
+        visits[start] = Some((0, None));
+
+        while let Some(node) = queue.pop_front() {
+            let distance = visits[node].map_or(0, |(distance, _)| distance);
+
+            for neighbour in &self.adjacency[node] {
+                if visits[*neighbour].is_none() {
+                    visits[*neighbour] = Some((distance + 1, Some(node)));
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+
+        visits
+    }
+
+    /// Writes the graph in Graphviz's DOT language, with every node and edge in the order it
+    /// was added.
+    #[must_use]
+    pub fn to_dot(&self, name: &str) -> String {
+        let (keyword, arrow) = match self.kind {
+            EdgeKind::Directed => ("digraph", "->"),
+            EdgeKind::Undirected => ("graph", "--"),
+        };
+
+        let mut dot = format!("{keyword} {} {{\n", dot_quoted(name));
+
+        for node in self.nodes.names() {
+            writeln!(dot, "    {};", dot_quoted(node)).unwrap();
+        }
+
+        if self.edge_count() > 0 {
+            dot.push('\n');
+        }
+
+        for (from, neighbours) in self.adjacency.iter().enumerate() {
+            for to in neighbours {
+                if self.kind == EdgeKind::Undirected && *to < from {
+                    continue;
+                }
+
+                writeln!(
+                    dot,
+                    "    {} {arrow} {};",
+                    dot_quoted(self.nodes.name(from)),
+                    dot_quoted(self.nodes.name(*to))
+                )
+                .unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// Orders the nodes `0..adjacency.len()` so that every edge goes forwards, with Kahn's
+/// algorithm, where `adjacency[node]` lists the nodes `node` has an edge to. For callers that
+/// already number their nodes and don't need a [`Graph`]. Whenever several nodes are ready,
+/// the lowest index goes first.
+///
+/// # Errors
+///
+/// If the edges form a cycle.
+///
+/// # Panics
+///
+/// If an edge goes to a node past the end of `adjacency`.
+pub fn topological_order(adjacency: &[Vec<usize>]) -> Result<Vec<usize>, GraphError> {
+    let mut waiting_on = vec![0; adjacency.len()];
+
+    for neighbours in adjacency {
+        for neighbour in neighbours {
+            waiting_on[*neighbour] += 1;
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..adjacency.len())
+        .filter(|node| waiting_on[*node] == 0)
+        .map(Reverse)
+        .collect();
+
+    let mut order = Vec::with_capacity(adjacency.len());
+
+    while let Some(Reverse(node)) = ready.pop() {
+        order.push(node);
+
+        for neighbour in &adjacency[node] {
+            waiting_on[*neighbour] -= 1;
+
+            if waiting_on[*neighbour] == 0 {
+                ready.push(Reverse(*neighbour));
+            }
+        }
+    }
+
+    if order.len() == adjacency.len() {
+        Ok(order)
+    } else {
+        Err(GraphError::Cycle(
+            (0..adjacency.len())
+                .filter(|node| waiting_on[*node] > 0)
+                .collect(),
+        ))
+    }
+}
+
+/// Same as [`Graph::strongly_connected_components`], for the nodes `0..adjacency.len()` as in
+/// [`topological_order`].
+///
+/// # Panics
+///
+/// If an edge goes to a node past the end of `adjacency`.
+#[must_use]
+pub fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan::new(adjacency.len());
+
+    for root in 0..adjacency.len() {
+        if tarjan.indices[root].is_none() {
+            tarjan.visit(adjacency, root);
+        }
+    }
+
+    tarjan.components
+}
+
+/// Quotes `id` for DOT, escaping any quotes and backslashes in it, so any name (even a
+/// keyword like `node`) can be used as an id.
+#[must_use]
+pub fn dot_quoted(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The state of Tarjan's algorithm, which numbers nodes in the order a depth-first search
+/// enters them and tracks the lowest number each can reach without leaving the search stack.
+struct Tarjan {
+    indices: Vec<Option<usize>>,
+    lowlinks: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+    next_index: usize,
+}
+
+impl Tarjan {
+    fn new(len: usize) -> Self {
+        Tarjan {
+            indices: vec![None; len],
+            lowlinks: vec![0; len],
+            on_stack: vec![false; len],
+            stack: Vec::new(),
+            components: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    fn enter(&mut self, node: usize) {
+        self.indices[node] = Some(self.next_index);
+        self.lowlinks[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    /// Searches from `root` without recursing, so long paths can't overflow the call stack.
+    fn visit(&mut self, adjacency: &[Vec<usize>], root: usize) {
+        // Each entry is a node being searched and how many of its edges have been followed.
+        let mut searching = vec![(root, 0)];
+
+        self.enter(root);
+
+        while let Some((node, followed)) = searching.last_mut() {
+            let node = *node;
+
+            if let Some(neighbour) = adjacency[node].get(*followed).copied() {
+                *followed += 1;
+
+                match self.indices[neighbour] {
+                    None => {
+                        self.enter(neighbour);
+                        searching.push((neighbour, 0));
+                    }
+                    Some(index) if self.on_stack[neighbour] => {
+                        self.lowlinks[node] = self.lowlinks[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+
+                continue;
+            }
+
+            searching.pop();
+
+            if let Some((parent, _)) = searching.last() {
+                self.lowlinks[*parent] = self.lowlinks[*parent].min(self.lowlinks[node]);
+            }
+
+            if Some(self.lowlinks[node]) == self.indices[node] {
+                let mut component = Vec::new();
+
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+
+                    if member == node {
+                        break;
+                    }
+                }
+
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directed(edges: &[(&str, &str)]) -> Graph {
+        let mut graph = Graph::directed();
+
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn test_node_table() {
+        let mut table = NodeTable::new();
+
+        assert!(table.is_empty());
+        assert_eq!(table.intern("b"), 0);
+        assert_eq!(table.intern("a"), 1);
+        assert_eq!(table.intern("b"), 0);
+        assert_eq!(table.index("a"), Some(1));
+        assert_eq!(table.index("c"), None);
+        assert_eq!(table.name(1), "a");
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.names().collect::<Vec<&str>>(), ["b", "a"]);
+    }
+
+    #[test]
+    fn test_add_edge() {
+        let mut directed = Graph::directed();
+        let mut undirected = Graph::undirected();
+
+        for graph in [&mut directed, &mut undirected] {
+            assert_eq!(graph.add_edge("a", "b"), (0, 1));
+            assert_eq!(graph.add_edge("b", "c"), (1, 2));
+            assert_eq!(graph.add_edge("a", "b"), (0, 1));
+            assert_eq!(graph.add_node("d"), 3);
+        }
+
+        assert_eq!(directed.neighbours(1), [2]);
+        assert_eq!(directed.edge_count(), 2);
+        assert!(!directed.has_edge(1, 0));
+
+        assert_eq!(undirected.neighbours(1), [0, 2]);
+        assert_eq!(undirected.edge_count(), 2);
+        assert!(undirected.has_edge(1, 0));
+        assert!(!undirected.has_edge(7, 0));
+
+        undirected.add_edge_between(3, 0);
+
+        assert_eq!(undirected.neighbours(0), [1, 3]);
+        assert_eq!(undirected.neighbours(3), [0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Both nodes must be in the graph!")]
+    fn test_add_edge_between_unknown_node() {
+        Graph::directed().add_edge_between(0, 0);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let graph = directed(&[("d", "b"), ("c", "b"), ("b", "a"), ("c", "a")]);
+
+        assert_eq!(graph.topological_sort(), Ok(vec![0, 2, 1, 3]));
+        assert_eq!(
+            topological_order(&[vec![2], vec![0, 2], vec![]]),
+            Ok(vec![1, 0, 2])
+        );
+        assert_eq!(
+            topological_order(&[vec![1], vec![0], vec![1]]),
+            Err(GraphError::Cycle(vec![0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_topological_sort_cycle() {
+        let graph = directed(&[("a", "b"), ("b", "c"), ("c", "b"), ("c", "d"), ("e", "d")]);
+
+        assert_eq!(
+            graph.topological_sort(),
+            Err(GraphError::Cycle(vec![1, 2, 3]))
+        );
+
+        let mut undirected = Graph::undirected();
+
+        undirected.add_edge("a", "b");
+
+        assert!(undirected.topological_sort().is_err());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let graph = directed(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "d"),
+            ("f", "f"),
+        ]);
+
+        let expected = vec![vec![3, 4], vec![0, 1, 2], vec![5]];
+
+        assert_eq!(graph.strongly_connected_components(), expected);
+    }
+
+    #[test]
+    fn test_bfs() {
+        let graph = directed(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("e", "a")]);
+
+        assert_eq!(
+            graph.bfs_distances(0),
+            [Some(0), Some(1), Some(1), Some(2), None]
+        );
+        assert_eq!(graph.shortest_path(0, 3), Some(vec![0, 1, 3]));
+        assert_eq!(graph.shortest_path(0, 0), Some(vec![0]));
+        assert_eq!(graph.shortest_path(0, 4), None);
+        assert_eq!(graph.shortest_path(0, 9), None);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Graph::undirected();
+
+        graph.add_edge("a", "b");
+        graph.add_edge("c", "a");
+        graph.add_node("d");
+
+        let expected = "graph \"lan\" {
+    \"a\";
+    \"b\";
+    \"c\";
+    \"d\";
+
+    \"a\" -- \"b\";
+    \"a\" -- \"c\";
+}
+";
+
+        assert_eq!(graph.to_dot("lan"), expected);
+        assert_eq!(
+            directed(&[("x", "y")]).to_dot("g"),
+            "digraph \"g\" {\n    \"x\";\n    \"y\";\n\n    \"x\" -> \"y\";\n}\n"
+        );
+
+        let escaped = r#"digraph "g" {
+    "say \"hi\"";
+    "a\\b";
+
+    "say \"hi\"" -> "a\\b";
+}
+"#;
+
+        assert_eq!(directed(&[(r#"say "hi""#, r"a\b")]).to_dot("g"), escaped);
+    }
+}
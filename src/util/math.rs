@@ -1,5 +1,8 @@
+use std::cmp::Ordering;
+use std::fmt;
 use std::mem;
-use std::ops::{Div, Mul, Neg, Rem};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
 
 pub fn gcd<T>(first: T, second: T) -> T
 where
@@ -46,12 +49,15 @@ where
 }
 
 #[must_use]
-pub fn extended_euclidean(left_coeff: i64, right_coeff: i64) -> (i64, i64, i64) {
+pub fn extended_euclidean<T>(left_coeff: T, right_coeff: T) -> (T, T, T)
+where
+    T: Sub<Output = T> + Mul<Output = T> + Div<Output = T> + PartialEq + Copy + From<u8>,
+{
     let (mut old_rem, mut rem) = (left_coeff, right_coeff);
-    let (mut old_s, mut s_coeff) = (1, 0);
-    let (mut old_t, mut t_coeff) = (0, 1);
+    let (mut old_s, mut s_coeff) = (T::from(1), T::from(0));
+    let (mut old_t, mut t_coeff) = (T::from(0), T::from(1));
 
-    while rem != 0 {
+    while rem != T::from(0) {
         let quotient = old_rem / rem;
 
         (old_rem, rem) = (rem, old_rem - quotient * rem);
@@ -103,6 +109,642 @@ pub fn min_positive_linear_diophantine(
     }
 }
 
+/// Computes `(left * right) mod modulus` without overflowing, even for moduli close to
+/// `i128::MAX`.
+///
+/// # Panics
+///
+/// If `modulus` isn't positive.
+#[must_use]
+pub fn mod_mul_i128(left: i128, right: i128, modulus: i128) -> i128 {
+    assert!(modulus > 0, "Modulus must be positive!");
+
+    let mut left = left.rem_euclid(modulus);
+    let mut right = right.rem_euclid(modulus);
+
+    if let Some(product) = left.checked_mul(right) {
+        return product % modulus;
+    }
+
+    let mut result = 0;
+
+    while right > 0 {
+        if right & 1 == 1 {
+            result = mod_add_i128(result, left, modulus);
+        }
+
+        left = mod_add_i128(left, left, modulus);
+        right >>= 1;
+    }
+
+    result
+}
+
+/// Both values must already be in `0..modulus`.
+fn mod_add_i128(left: i128, right: i128, modulus: i128) -> i128 {
+    if left >= modulus - right {
+        left - (modulus - right)
+    } else {
+        left + right
+    }
+}
+
+/// Computes `base^exponent mod modulus` by repeated squaring.
+///
+/// # Panics
+///
+/// If `modulus` isn't positive.
+#[must_use]
+pub fn mod_pow(base: i64, exponent: u64, modulus: i64) -> i64 {
+    let result = mod_pow_i128(i128::from(base), u128::from(exponent), i128::from(modulus));
+
+    i64::try_from(result).expect("Result is less than an i64 modulus!")
+}
+
+/// Computes `base^exponent mod modulus` by repeated squaring.
+///
+/// # Panics
+///
+/// If `modulus` isn't positive.
+#[must_use]
+pub fn mod_pow_i128(base: i128, exponent: u128, modulus: i128) -> i128 {
+    assert!(modulus > 0, "Modulus must be positive!");
+
+    let mut base = base.rem_euclid(modulus);
+    let mut exponent = exponent;
+    let mut result = 1 % modulus;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul_i128(result, base, modulus);
+        }
+
+        base = mod_mul_i128(base, base, modulus);
+        exponent >>= 1;
+    }
+
+    result
+}
+
+/// Returns `x` in `0..modulus` such that `value * x = 1 (mod modulus)`, if `value` and
+/// `modulus` are coprime.
+///
+/// # Panics
+///
+/// If `modulus` isn't positive.
+#[must_use]
+pub fn mod_inverse(value: i64, modulus: i64) -> Option<i64> {
+    mod_inverse_i128(i128::from(value), i128::from(modulus))
+        .map(|inverse| i64::try_from(inverse).expect("Inverse is less than an i64 modulus!"))
+}
+
+/// Returns `x` in `0..modulus` such that `value * x = 1 (mod modulus)`, if `value` and
+/// `modulus` are coprime.
+///
+/// # Panics
+///
+/// If `modulus` isn't positive.
+#[must_use]
+pub fn mod_inverse_i128(value: i128, modulus: i128) -> Option<i128> {
+    assert!(modulus > 0, "Modulus must be positive!");
+
+    let (gcd, coeff, _) = extended_euclidean(value.rem_euclid(modulus), modulus);
+
+    if gcd == 1 {
+        Some(coeff.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+/// Solves the system `x = residue (mod modulus)` for every `(residue, modulus)` in
+/// `congruences`, returning `(x, lcm of moduli)` with `x` in `0..lcm`.
+///
+/// The moduli don't have to be coprime. Returns `None` if the congruences contradict each
+/// other, or if the combined modulus doesn't fit in an `i64`.
+///
+/// # Panics
+///
+/// If any modulus isn't positive.
+#[must_use]
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let wide_congruences: Vec<(i128, i128)> = congruences
+        .iter()
+        .map(|(residue, modulus)| (i128::from(*residue), i128::from(*modulus)))
+        .collect();
+
+    let (residue, modulus) = crt_i128(&wide_congruences)?;
+
+    Some((i64::try_from(residue).ok()?, i64::try_from(modulus).ok()?))
+}
+
+/// Solves the system `x = residue (mod modulus)` for every `(residue, modulus)` in
+/// `congruences`, returning `(x, lcm of moduli)` with `x` in `0..lcm`.
+///
+/// The moduli don't have to be coprime. Returns `None` if the congruences contradict each
+/// other, or if the combined modulus doesn't fit in an `i128`.
+///
+/// # Panics
+///
+/// If any modulus isn't positive.
+#[must_use]
+pub fn crt_i128(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    let mut result = (0, 1);
+
+    for (residue, modulus) in congruences {
+        assert!(*modulus > 0, "Modulus must be positive!");
+
+        let (current_residue, current_modulus) = result;
+        let residue = residue.rem_euclid(*modulus);
+
+        let common_divisor = gcd(current_modulus, *modulus);
+        let difference = residue - current_residue;
+
+        if difference % common_divisor != 0 {
+            return None;
+        }
+
+        let reduced_modulus = modulus / common_divisor;
+        let combined_modulus = current_modulus.checked_mul(reduced_modulus)?;
+
+        // Find k such that current_residue + current_modulus * k = residue (mod modulus).
+        let inverse = mod_inverse_i128(current_modulus / common_divisor, reduced_modulus)
+            .expect("Reduced moduli are coprime!");
+        let steps = mod_mul_i128(difference / common_divisor, inverse, reduced_modulus);
+
+        result = (current_residue + current_modulus * steps, combined_modulus);
+    }
+
+    Some(result)
+}
+
+/// The integer operations [`Rational`] needs, implemented for every type that has them.
+pub trait RationalInteger:
+    Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+    + Ord
+    + Copy
+    + From<u8>
+{
+}
+
+impl<T> RationalInteger for T where
+    T: Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Rem<Output = T>
+        + Neg<Output = T>
+        + Ord
+        + Copy
+        + From<u8>
+{
+}
+
+/// An exact fraction, always stored fully reduced with a positive denominator.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Rational<T>
+where
+    T: RationalInteger,
+{
+    numerator: T,
+    denominator: T,
+}
+
+impl<T> Rational<T>
+where
+    T: RationalInteger,
+{
+    /// # Panics
+    ///
+    /// If `denominator` is zero.
+    pub fn new(numerator: T, denominator: T) -> Self {
+        let zero = T::from(0);
+
+        assert!(denominator != zero, "Denominator cannot be zero!");
+
+        if numerator == zero {
+            return Rational {
+                numerator: zero,
+                denominator: T::from(1),
+            };
+        }
+
+        let common_divisor = gcd(numerator, denominator);
+
+        let (numerator, denominator) = (numerator / common_divisor, denominator / common_divisor);
+
+        if denominator < zero {
+            Rational {
+                numerator: -numerator,
+                denominator: -denominator,
+            }
+        } else {
+            Rational {
+                numerator,
+                denominator,
+            }
+        }
+    }
+
+    pub fn numerator(&self) -> T {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> T {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == T::from(1)
+    }
+
+    pub fn to_integer(&self) -> Option<T> {
+        self.is_integer().then_some(self.numerator)
+    }
+
+    /// # Panics
+    ///
+    /// If `self` is zero.
+    #[must_use]
+    pub fn recip(&self) -> Self {
+        Rational::new(self.denominator, self.numerator)
+    }
+
+    /// Largest integer less than or equal to `self`.
+    pub fn floor(&self) -> T {
+        let quotient = self.numerator / self.denominator;
+
+        if self.numerator < T::from(0) && self.numerator % self.denominator != T::from(0) {
+            quotient - T::from(1)
+        } else {
+            quotient
+        }
+    }
+}
+
+impl<T> From<T> for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn from(value: T) -> Self {
+        Rational {
+            numerator: value,
+            denominator: T::from(1),
+        }
+    }
+}
+
+impl<T> Add for Rational<T>
+where
+    T: RationalInteger,
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let denominator = lcm(self.denominator, rhs.denominator);
+
+        Rational::new(
+            self.numerator * (denominator / self.denominator)
+                + rhs.numerator * (denominator / rhs.denominator),
+            denominator,
+        )
+    }
+}
+
+impl<T> AddAssign for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T> Sub for Rational<T>
+where
+    T: RationalInteger,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<T> SubAssign for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T> Mul for Rational<T>
+where
+    T: RationalInteger,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational::new(
+            self.numerator * rhs.numerator,
+            self.denominator * rhs.denominator,
+        )
+    }
+}
+
+impl<T> MulAssign for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T> Div for Rational<T>
+where
+    T: RationalInteger,
+{
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.recip()
+    }
+}
+
+impl<T> DivAssign for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<T> Neg for Rational<T>
+where
+    T: RationalInteger,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl<T> PartialOrd for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Rational<T>
+where
+    T: RationalInteger,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are always positive, so cross multiplying keeps the order.
+        (self.numerator * other.denominator).cmp(&(other.numerator * self.denominator))
+    }
+}
+
+impl<T> fmt::Display for Rational<T>
+where
+    T: RationalInteger + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RationalParseError;
+
+impl<T> FromStr for Rational<T>
+where
+    T: RationalInteger + FromStr,
+{
+    type Err = RationalParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (numerator_str, denominator_str) = input.split_once('/').unwrap_or((input, "1"));
+
+        let numerator: T = numerator_str.parse().map_err(|_| RationalParseError)?;
+        let denominator: T = denominator_str.parse().map_err(|_| RationalParseError)?;
+
+        if denominator == T::from(0) {
+            return Err(RationalParseError);
+        }
+
+        Ok(Rational::new(numerator, denominator))
+    }
+}
+
+/// The outcome of solving a system of integer linear equations exactly.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LinearSystemSolution {
+    /// Every variable is `numerators[i] / denominator`, with `denominator > 0` and the
+    /// fraction fully reduced.
+    Unique {
+        numerators: Vec<i128>,
+        denominator: i128,
+    },
+    NoSolution,
+    InfinitelyMany,
+}
+
+impl LinearSystemSolution {
+    fn unique(mut numerators: Vec<i128>, mut denominator: i128) -> Self {
+        if denominator < 0 {
+            denominator = -denominator;
+            for numerator in &mut numerators {
+                *numerator = -*numerator;
+            }
+        }
+
+        let common_divisor = numerators
+            .iter()
+            .filter(|numerator| **numerator != 0)
+            .fold(denominator, |divisor, numerator| gcd(divisor, *numerator));
+
+        LinearSystemSolution::Unique {
+            numerators: numerators
+                .into_iter()
+                .map(|numerator| numerator / common_divisor)
+                .collect(),
+            denominator: denominator / common_divisor,
+        }
+    }
+
+    /// Returns the solution if it is unique and every variable is an integer.
+    #[must_use]
+    pub fn integer_solution(&self) -> Option<Vec<i128>> {
+        match self {
+            LinearSystemSolution::Unique {
+                numerators,
+                denominator: 1,
+            } => Some(numerators.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Solves `coefficients * [x, y] = constants` exactly with Cramer's rule.
+#[must_use]
+pub fn solve_2x2_linear_system(
+    coefficients: [[i128; 2]; 2],
+    constants: [i128; 2],
+) -> LinearSystemSolution {
+    let [[x_0, y_0], [x_1, y_1]] = coefficients;
+    let [constant_0, constant_1] = constants;
+
+    let determinant = x_0 * y_1 - y_0 * x_1;
+
+    if determinant == 0 {
+        return solve_linear_system(&[vec![x_0, y_0], vec![x_1, y_1]], &constants);
+    }
+
+    LinearSystemSolution::unique(
+        vec![
+            constant_0 * y_1 - y_0 * constant_1,
+            x_0 * constant_1 - constant_0 * x_1,
+        ],
+        determinant,
+    )
+}
+
+/// Solves `coefficients * x = constants` exactly with fraction-free Gauss-Jordan elimination.
+///
+/// Each row of `coefficients` is one equation, so the system doesn't have to be square.
+///
+/// # Panics
+///
+/// If `coefficients` and `constants` have a different number of rows, or the rows of
+/// `coefficients` have different lengths.
+#[must_use]
+pub fn solve_linear_system(coefficients: &[Vec<i128>], constants: &[i128]) -> LinearSystemSolution {
+    assert_eq!(
+        coefficients.len(),
+        constants.len(),
+        "Every equation needs a constant!"
+    );
+
+    let number_of_variables = coefficients.first().map_or(0, Vec::len);
+
+    let mut rows: Vec<Vec<i128>> = coefficients
+        .iter()
+        .zip(constants)
+        .map(|(row, constant)| {
+            assert_eq!(row.len(), number_of_variables, "Ragged coefficients!");
+
+            let mut augmented_row = row.clone();
+
+            augmented_row.push(*constant);
+
+            augmented_row
+        })
+        .collect();
+
+    let mut pivot_row = 0;
+
+    for column in 0..number_of_variables {
+        let Some(found_row) = (pivot_row..rows.len()).find(|&row| rows[row][column] != 0) else {
+            continue;
+        };
+
+        rows.swap(pivot_row, found_row);
+
+        let pivot = rows[pivot_row].clone();
+
+        for (row_index, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+
+            if row_index == pivot_row || factor == 0 {
+                continue;
+            }
+
+            row.iter_mut().zip(&pivot).for_each(|(value, pivot_value)| {
+                *value = *value * pivot[column] - pivot_value * factor;
+            });
+
+            reduce_row(row);
+        }
+
+        pivot_row += 1;
+    }
+
+    // Every row past the last pivot has all-zero coefficients, so a non-zero constant there
+    // reads `0 = c`.
+    if rows[pivot_row..]
+        .iter()
+        .any(|row| row[number_of_variables] != 0)
+    {
+        return LinearSystemSolution::NoSolution;
+    }
+
+    if pivot_row < number_of_variables {
+        return LinearSystemSolution::InfinitelyMany;
+    }
+
+    let fractions: Vec<(i128, i128)> = rows
+        .iter()
+        .take(number_of_variables)
+        .enumerate()
+        .map(|(variable, row)| (row[number_of_variables], row[variable]))
+        .collect();
+
+    let denominator = fractions
+        .iter()
+        .fold(1, |denominator, (_, row_denominator)| {
+            lcm(denominator, row_denominator.abs())
+        });
+
+    LinearSystemSolution::unique(
+        fractions
+            .into_iter()
+            .map(|(numerator, row_denominator)| numerator * (denominator / row_denominator))
+            .collect(),
+        denominator,
+    )
+}
+
+fn reduce_row(row: &mut [i128]) {
+    let common_divisor = row
+        .iter()
+        .filter(|value| **value != 0)
+        .fold(0, |divisor, value| {
+            if divisor == 0 {
+                value.abs()
+            } else {
+                gcd(divisor, *value)
+            }
+        });
+
+    if common_divisor > 1 {
+        for value in row.iter_mut() {
+            *value /= common_divisor;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +803,318 @@ mod tests {
         assert_eq!(min_positive_linear_diophantine(3, 50, 44), None);
         assert_eq!(min_positive_linear_diophantine(50, 3, 44), None);
     }
+
+    #[test]
+    fn test_solve_2x2_linear_system_unique() {
+        let expected = LinearSystemSolution::Unique {
+            numerators: vec![80, 40],
+            denominator: 1,
+        };
+
+        let result = solve_2x2_linear_system([[94, 22], [34, 67]], [8_400, 5_400]);
+
+        assert_eq!(result, expected);
+        assert_eq!(result.integer_solution(), Some(vec![80, 40]));
+    }
+
+    #[test]
+    fn test_solve_2x2_linear_system_unique_not_integer() {
+        let expected = LinearSystemSolution::Unique {
+            numerators: vec![1, 1],
+            denominator: 2,
+        };
+
+        let result = solve_2x2_linear_system([[1, 1], [1, -1]], [1, 0]);
+
+        assert_eq!(result, expected);
+        assert_eq!(result.integer_solution(), None);
+    }
+
+    #[test]
+    fn test_solve_2x2_linear_system_beyond_f64_precision() {
+        let offset = 10_000_000_000_000;
+
+        let expected = Some(vec![118_679_050_709, 103_199_174_542]);
+
+        let result =
+            solve_2x2_linear_system([[26, 67], [66, 21]], [12_748 + offset, 12_176 + offset]);
+
+        assert_eq!(result.integer_solution(), expected);
+    }
+
+    #[test]
+    fn test_solve_2x2_linear_system_singular() {
+        assert_eq!(
+            solve_2x2_linear_system([[1, 2], [2, 4]], [3, 6]),
+            LinearSystemSolution::InfinitelyMany
+        );
+        assert_eq!(
+            solve_2x2_linear_system([[1, 2], [2, 4]], [3, 7]),
+            LinearSystemSolution::NoSolution
+        );
+        assert_eq!(
+            solve_2x2_linear_system([[0, 0], [0, 0]], [1, 0]),
+            LinearSystemSolution::NoSolution
+        );
+    }
+
+    #[test]
+    fn test_solve_linear_system_unique() {
+        let expected = LinearSystemSolution::Unique {
+            numerators: vec![2, 3, -1],
+            denominator: 1,
+        };
+
+        let result = solve_linear_system(
+            &[vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]],
+            &[8, -11, -3],
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_solve_linear_system_unique_common_denominator() {
+        let expected = LinearSystemSolution::Unique {
+            numerators: vec![3, 2, 0],
+            denominator: 6,
+        };
+
+        let result =
+            solve_linear_system(&[vec![2, 0, 0], vec![0, 3, 0], vec![0, 0, 5]], &[1, 1, 0]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_solve_linear_system_no_solution() {
+        let expected = LinearSystemSolution::NoSolution;
+
+        let result =
+            solve_linear_system(&[vec![1, 1, 1], vec![1, 2, 3], vec![2, 3, 4]], &[1, 2, 4]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_solve_linear_system_infinitely_many() {
+        let expected = LinearSystemSolution::InfinitelyMany;
+
+        let result =
+            solve_linear_system(&[vec![1, 1, 1], vec![1, 2, 3], vec![2, 3, 4]], &[1, 2, 3]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_solve_linear_system_overdetermined() {
+        let expected = LinearSystemSolution::Unique {
+            numerators: vec![1, 2],
+            denominator: 1,
+        };
+
+        let result = solve_linear_system(&[vec![1, 0], vec![0, 1], vec![1, 1]], &[1, 2, 3]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_mod_mul_i128() {
+        let modulus = i128::MAX - 2;
+
+        assert_eq!(mod_mul_i128(7, 8, 5), 1);
+        assert_eq!(mod_mul_i128(-7, 8, 5), 4);
+        assert_eq!(mod_mul_i128(modulus - 1, modulus - 1, modulus), 1);
+        assert_eq!(mod_mul_i128(modulus - 1, 2, modulus), modulus - 2);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+        assert_eq!(mod_pow(10, 0, 7), 1);
+        assert_eq!(mod_pow(10, 0, 1), 0);
+        assert_eq!(mod_pow(3, u64::MAX, 1_000_000_007), 35_072_593);
+        assert_eq!(mod_pow(i64::MAX - 1, 2, i64::MAX), 1);
+    }
+
+    #[test]
+    fn test_mod_pow_i128() {
+        // Fermat's little theorem with the Mersenne prime 2^127 - 1.
+        let prime = i128::MAX;
+
+        assert_eq!(
+            mod_pow_i128(123_456_789, (prime - 1).unsigned_abs(), prime),
+            1
+        );
+        assert_eq!(mod_pow_i128(2, 127, prime), 1);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(10, 17), Some(12));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(101, 103), Some(51));
+    }
+
+    #[test]
+    fn test_mod_inverse_i128() {
+        let prime = i128::MAX;
+        let value = 987_654_321_987_654_321;
+
+        let inverse = mod_inverse_i128(value, prime).unwrap();
+
+        assert_eq!(mod_mul_i128(value, inverse, prime), 1);
+        assert_eq!(mod_inverse_i128(4, 1 << 100), None);
+    }
+
+    #[test]
+    fn test_crt_coprime() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 3), (-2, 5)]), Some((8, 15)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_crt_robot_axes() {
+        let expected = Some((7_687, 101 * 103));
+
+        let result = crt(&[(7_687 % 101, 101), (7_687 % 103, 103)]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_crt_not_coprime() {
+        assert_eq!(crt(&[(2, 6), (8, 10)]), Some((8, 30)));
+        assert_eq!(crt(&[(3, 4), (5, 6), (7, 8)]), Some((23, 24)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+    }
+
+    #[test]
+    fn test_crt_overflow() {
+        let large_prime = 1_000_000_007;
+        let other_large_prime = 998_244_353;
+        let congruences = [
+            (5, large_prime),
+            (7, other_large_prime),
+            (11, 1_000_000_009),
+        ];
+
+        assert_eq!(crt(&congruences), None);
+
+        let wide_congruences: Vec<(i128, i128)> = congruences
+            .iter()
+            .map(|(residue, modulus)| (i128::from(*residue), i128::from(*modulus)))
+            .collect();
+
+        let (residue, modulus) = crt_i128(&wide_congruences).unwrap();
+
+        assert_eq!(
+            modulus,
+            i128::from(large_prime) * i128::from(other_large_prime) * 1_000_000_009
+        );
+        assert!(wide_congruences
+            .iter()
+            .all(|(expected, modulus_i)| residue % modulus_i == *expected));
+    }
+
+    #[test]
+    fn test_rational_new_normalises() {
+        let expected = Rational {
+            numerator: -2,
+            denominator: 3,
+        };
+
+        assert_eq!(Rational::new(4, -6), expected);
+        assert_eq!(Rational::new(-4, 6), expected);
+        assert_eq!(Rational::new(0, -6), Rational::from(0));
+        assert_eq!(Rational::new(-4, -6), -expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "Denominator cannot be zero!")]
+    fn test_rational_new_zero_denominator() {
+        let _ = Rational::new(1, 0);
+    }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        let half = Rational::new(1, 2);
+        let third = Rational::new(1, 3);
+
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(third - half, Rational::new(-1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert_eq!(-half, Rational::new(-1, 2));
+        assert_eq!(Rational::new(2, 3).recip(), Rational::new(3, 2));
+    }
+
+    #[test]
+    fn test_rational_arithmetic_assign() {
+        let mut result = Rational::new(3, 4);
+
+        result += Rational::new(1, 4);
+        assert_eq!(result, Rational::from(1));
+
+        result -= Rational::new(1, 3);
+        assert_eq!(result, Rational::new(2, 3));
+
+        result *= Rational::new(9, 4);
+        assert_eq!(result, Rational::new(3, 2));
+
+        result /= Rational::new(-3, 1);
+        assert_eq!(result, Rational::new(-1, 2));
+    }
+
+    #[test]
+    fn test_rational_integer_parts() {
+        assert_eq!(Rational::new(6, 3).to_integer(), Some(2));
+        assert_eq!(Rational::new(7, 3).to_integer(), None);
+        assert_eq!(Rational::new(7, 3).floor(), 2);
+        assert_eq!(Rational::new(-7, 3).floor(), -3);
+        assert_eq!(Rational::new(-6, 3).floor(), -2);
+    }
+
+    #[test]
+    fn test_rational_ordering() {
+        let mut values = vec![
+            Rational::new(1, 2),
+            Rational::new(-3, 4),
+            Rational::new(2, 3),
+            Rational::from(0),
+        ];
+
+        values.sort();
+
+        assert_eq!(
+            values,
+            vec![
+                Rational::new(-3, 4),
+                Rational::from(0),
+                Rational::new(1, 2),
+                Rational::new(2, 3),
+            ]
+        );
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+    }
+
+    #[test]
+    fn test_rational_display() {
+        assert_eq!(Rational::new(6, -4).to_string(), "-3/2");
+        assert_eq!(Rational::new(8, 4).to_string(), "2");
+    }
+
+    #[test]
+    fn test_rational_from_str() {
+        assert_eq!("6/-4".parse::<Rational<i64>>(), Ok(Rational::new(-3, 2)));
+        assert_eq!("-5".parse::<Rational<i64>>(), Ok(Rational::from(-5)));
+        assert_eq!("1/0".parse::<Rational<i64>>(), Err(RationalParseError));
+        assert_eq!("1/a".parse::<Rational<i64>>(), Err(RationalParseError));
+        assert_eq!("".parse::<Rational<i64>>(), Err(RationalParseError));
+    }
 }
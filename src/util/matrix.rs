@@ -0,0 +1,364 @@
+use std::ops::{Add, Index, IndexMut, Mul, Rem};
+
+/// A small dense matrix stored in row-major order.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    /// # Panics
+    ///
+    /// If `data` doesn't hold exactly `rows * cols` values.
+    #[must_use]
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        assert_eq!(data.len(), rows * cols, "Data doesn't fit the dimensions!");
+
+        Matrix { rows, cols, data }
+    }
+
+    #[must_use]
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix::new(rows, cols, vec![T::from(0); rows * cols])
+    }
+
+    #[must_use]
+    pub fn identity(size: usize) -> Self {
+        let mut result = Matrix::zeros(size, size);
+
+        for index in 0..size {
+            result[(index, index)] = T::from(1);
+        }
+
+        result
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[must_use]
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if (row < self.rows) && (col < self.cols) {
+            self.data.get(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If `self.cols()` doesn't match `other.rows()`.
+    #[must_use]
+    pub fn multiply(&self, other: &Self) -> Self {
+        self.multiply_with(other, |value| value)
+    }
+
+    /// Multiplies `self` by the column vector `vector`.
+    ///
+    /// # Panics
+    ///
+    /// If `self.cols()` doesn't match the length of `vector`.
+    #[must_use]
+    pub fn multiply_vector(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.cols, vector.len(), "Vector doesn't fit the matrix!");
+
+        // An empty row sums to zero, and `chunks` can't split the data into empty rows.
+        if self.cols == 0 {
+            return vec![T::from(0); self.rows];
+        }
+
+        self.data
+            .chunks(self.cols)
+            .map(|row| {
+                row.iter()
+                    .zip(vector)
+                    .fold(T::from(0), |sum, (left, right)| sum + *left * *right)
+            })
+            .collect()
+    }
+
+    /// Raises a square matrix to `exponent` by repeated squaring.
+    ///
+    /// # Panics
+    ///
+    /// If `self` isn't square.
+    #[must_use]
+    pub fn pow(&self, exponent: u64) -> Self {
+        self.pow_with(exponent, |value| value)
+    }
+
+    fn multiply_with<R>(&self, other: &Self, reduce: R) -> Self
+    where
+        R: Fn(T) -> T,
+    {
+        assert_eq!(self.cols, other.rows, "Matrices can't be multiplied!");
+
+        let mut result = Matrix::zeros(self.rows, other.cols);
+
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                result[(row, col)] = (0..self.cols).fold(T::from(0), |sum, index| {
+                    reduce(sum + reduce(self[(row, index)] * other[(index, col)]))
+                });
+            }
+        }
+
+        result
+    }
+
+    fn pow_with<R>(&self, exponent: u64, reduce: R) -> Self
+    where
+        R: Fn(T) -> T,
+    {
+        assert!(
+            self.is_square(),
+            "Only square matrices can be raised to a power!"
+        );
+
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+
+        result.data = result.data.into_iter().map(&reduce).collect();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply_with(&base, &reduce);
+            }
+
+            exponent >>= 1;
+
+            if exponent > 0 {
+                base = base.multiply_with(&base, &reduce);
+            }
+        }
+
+        result
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Rem<Output = T> + Copy + From<u8>,
+{
+    /// Same as [`Matrix::multiply`], but every value is reduced by `modulus` as it's computed.
+    ///
+    /// Values must already be non-negative and less than `modulus`, and `modulus^2` must fit
+    /// in `T`.
+    ///
+    /// # Panics
+    ///
+    /// If `self.cols()` doesn't match `other.rows()`.
+    #[must_use]
+    pub fn mod_multiply(&self, other: &Self, modulus: T) -> Self {
+        self.multiply_with(other, |value| value % modulus)
+    }
+
+    /// Same as [`Matrix::pow`], but every value is reduced by `modulus` as it's computed.
+    ///
+    /// Values must already be non-negative and less than `modulus`, and `modulus^2` must fit
+    /// in `T`.
+    ///
+    /// # Panics
+    ///
+    /// If `self` isn't square.
+    #[must_use]
+    pub fn mod_pow(&self, exponent: u64, modulus: T) -> Self {
+        self.pow_with(exponent, |value| value % modulus)
+    }
+}
+
+impl<T> From<Vec<Vec<T>>> for Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    fn from(input: Vec<Vec<T>>) -> Self {
+        let rows = input.len();
+        let cols = input.first().map_or(0, Vec::len);
+
+        Matrix::new(rows, cols, input.into_iter().flatten().collect())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        self.get(row, col).expect("Index out of bounds!")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        assert!(
+            (row < self.rows) && (col < self.cols),
+            "Index out of bounds!"
+        );
+
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl<T> Mul for &Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.multiply(rhs)
+    }
+}
+
+impl<T> Mul for Matrix<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy + From<u8>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.multiply(&rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_nested_vec() {
+        let expected = Matrix {
+            rows: 2,
+            cols: 3,
+            data: vec![1, 2, 3, 4, 5, 6],
+        };
+
+        let result = Matrix::from(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        assert_eq!(result, expected);
+        assert_eq!(result[(1, 0)], 4);
+        assert_eq!(result.get(2, 0), None);
+        assert_eq!(result.get(0, 3), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Data doesn't fit the dimensions!")]
+    fn test_new_ragged() {
+        let _ = Matrix::from(vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_identity() {
+        let expected = Matrix::from(vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]]);
+
+        assert_eq!(Matrix::<u64>::identity(3), expected);
+    }
+
+    #[test]
+    fn test_multiply() {
+        let left = Matrix::from(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        let right = Matrix::from(vec![vec![7, 8], vec![9, 10], vec![11, 12]]);
+
+        let expected = Matrix::from(vec![vec![58, 64], vec![139, 154]]);
+
+        assert_eq!(&left * &right, expected);
+        assert_eq!(left.multiply(&Matrix::identity(3)), left);
+        assert_eq!(Matrix::identity(2) * left.clone(), left);
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrices can't be multiplied!")]
+    fn test_multiply_mismatch() {
+        let left = Matrix::from(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        let _ = &left * &left;
+    }
+
+    #[test]
+    fn test_multiply_vector() {
+        let matrix = Matrix::from(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        assert_eq!(matrix.multiply_vector(&[1, 0, -1]), vec![-2, -2]);
+    }
+
+    #[test]
+    fn test_multiply_vector_no_columns() {
+        let matrix: Matrix<i64> = Matrix::zeros(3, 0);
+
+        assert_eq!(matrix.multiply_vector(&[]), vec![0, 0, 0]);
+        assert_eq!(
+            Matrix::<i64>::zeros(0, 0).multiply_vector(&[]),
+            Vec::<i64>::new()
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        let fibonacci = Matrix::from(vec![vec![1_u64, 1], vec![1, 0]]);
+
+        assert_eq!(fibonacci.pow(0), Matrix::identity(2));
+        assert_eq!(fibonacci.pow(1), fibonacci);
+        assert_eq!(fibonacci.pow(90)[(0, 1)], 2_880_067_194_370_816_120);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only square matrices can be raised to a power!")]
+    fn test_pow_not_square() {
+        let _ = Matrix::from(vec![vec![1, 2, 3]]).pow(2);
+    }
+
+    #[test]
+    fn test_mod_multiply() {
+        let left = Matrix::from(vec![vec![6_u64, 5], vec![4, 3]]);
+
+        let expected = Matrix::from(vec![vec![0, 3], vec![1, 1]]);
+
+        assert_eq!(left.mod_multiply(&left, 7), expected);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        let fibonacci = Matrix::from(vec![vec![1_u64, 1], vec![1, 0]]);
+
+        let result = fibonacci.mod_pow(1_000_000_000_000_000_000, 1_000_000_007);
+
+        assert_eq!(result[(0, 1)], 209_783_453);
+        assert_eq!(fibonacci.mod_pow(0, 1), Matrix::zeros(2, 2));
+    }
+
+    #[test]
+    fn test_mod_pow_linear_recurrence() {
+        // Two kinds of stone: a 0 becomes a 1, and a 1 splits into a 0 and a 1.
+        let transitions = Matrix::from(vec![vec![0_u64, 1], vec![1, 1]]);
+
+        let blinked = transitions.mod_pow(5, 1_000);
+
+        assert_eq!(blinked.multiply_vector(&[1, 0]), vec![3, 5]);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod cycle;
 pub mod file_reader;
+pub mod graph;
 pub mod grid;
 pub mod location;
 pub mod math;
+pub mod matrix;
 pub mod point_2d;